@@ -1,59 +1,152 @@
-use std::{error, fs};
+use std::{env, error, fs};
+use std::io::{IsTerminal, stdin};
+use std::path::Path;
 
 use clap::{
+    Arg,
+    ArgAction,
+    ArgMatches,
     Command,
+    FromArgMatches,
+    Subcommand,
     crate_description,
     crate_name,
-    crate_version
+    crate_version,
+    error::ErrorKind,
 };
 use rustyline::{Editor, error::ReadlineError};
 
 mod rx;
 use crate::rx::Datasets;
-use crate::rx::{cmd, io, repl};
+use crate::rx::cmd::{session, Status};
+use crate::rx::{batch, cmd, config, io, repl};
 
-/// Name of the file where REPL history is stored.
+/// Name of the file where REPL history is stored, unless set by
+/// `repl.history`.
 const HISTORY_FILE: &str = ".cone_history";
 
 /// Code of the current program.
 const APP_CODE: &str = "RX-01";
 
+/// Argument separating the commands chained on the command line.
+const CHAIN_SEPARATOR: &str = "then";
+
 /// Builds the CLI metadata using `clap`.
+///
+/// Every REPL command is also available as a subcommand, so it can be
+/// run directly from the shell, chained to others with `then`.
 fn build_cli() -> Command {
-    Command::new(crate_name!())
+    let cli = Command::new(crate_name!())
         .version(crate_version!())
+        .disable_help_subcommand(true)
         .about(format!("{}.", crate_description!()))
+        .after_help(format!(
+            "Commands can be chained with '{CHAIN_SEPARATOR}', as in \
+             'cone load -f a.csv {CHAIN_SEPARATOR} preview A'."
+        ))
+        .arg(
+            Arg::new("command")
+                .short('c')
+                .long("command")
+                .value_name("COMMANDS")
+                .help("Execute commands separated by ';' and exit"),
+        )
+        .arg(
+            Arg::new("restore")
+                .long("restore")
+                .action(ArgAction::SetTrue)
+                .help("Restore the session saved when the REPL last exited (see 'repl.autosave')"),
+        )
+        .arg(
+            Arg::new("input")
+                .long("input")
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Load a file before running commands (repeatable)"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_name("PATH")
+                .global(true)
+                .help("Save every dataset to a file or directory after running commands"),
+        )
+        .subcommand(
+            Command::new("run")
+                .about("Execute a script of REPL commands ('-' reads stdin)")
+                .arg(Arg::new("script").value_name("SCRIPT").required(true)),
+        );
+
+    cmd::Commands::augment_subcommands(cli)
+}
+
+/// Parses command-line arguments into one set of matches per chained
+/// command, paired with the arguments it was parsed from.
+///
+/// Commands are chained with `then`, as in `cone load -f a.csv then
+/// preview A`, so argument values named like a command never start one.
+fn parse_chain(args: &[String]) -> Result<Vec<(String, ArgMatches)>, clap::Error> {
+    let (bin, rest) = args.split_first().map_or(("cone", &[][..]), |(b, r)| (b.as_str(), r));
+
+    rest.split(|arg| arg == CHAIN_SEPARATOR)
+        .enumerate()
+        .map(|(i, segment)| {
+            let argv = std::iter::once(bin).chain(segment.iter().map(String::as_str));
+            let matches = build_cli().try_get_matches_from(argv)?;
+            if i > 0 && matches.subcommand().is_none() {
+                return Err(build_cli().error(
+                    ErrorKind::MissingSubcommand,
+                    format!("expected a command after '{CHAIN_SEPARATOR}'"),
+                ));
+            }
+            let text = shlex::try_join(segment.iter().map(String::as_str)).unwrap_or_default();
+            Ok((text, matches))
+        })
+        .collect()
 }
 
-/// Runs the main application loop and REPL interface.
-fn run_app() -> Result<(), Box<dyn error::Error>> {
-    // accepted REPL commands (used for hinting)
-    let commands = [
-        "clean", "compute", "exit", "help",
-        "load", "preview", "save",
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect::<Vec<_>>();
-
-    let helper = repl::ReadLineHelper::new(commands);
+/// Runs the main application loop and REPL interface, starting with
+/// the given datasets.
+///
+/// The datasets in memory are saved as the last session upon exit when
+/// `repl.autosave` is set.
+fn run_app(mut datasets: Datasets, inputs: &[String]) -> Result<(), Box<dyn error::Error>> {
+    let helper = repl::ReadLineHelper::new();
     let mut rl = Editor::new()?;
     rl.set_helper(Some(helper));
 
     io::header(APP_CODE);
 
-    if rl.load_history(HISTORY_FILE).is_err() {
+    for path in &config::get().files {
+        io::print_info(format!("settings ← '{}' loaded", path.display()));
+    }
+
+    let history_file = config::get()
+        .repl
+        .history
+        .clone()
+        .unwrap_or_else(|| HISTORY_FILE.into());
+
+    if rl.load_history(&history_file).is_err() {
         println!();
-        io::print_warn(format!("no '{HISTORY_FILE}' file found"));
-        let _ = fs::File::create(HISTORY_FILE)
+        io::print_warn(format!("no '{}' file found", history_file.display()));
+        let _ = fs::File::create(&history_file)
             .map(|_| io::print_info("history file created"))
             .map_err(|_| io::print_error("failed to create history file"));
     }
 
-    // initialize in-memory dataset collection
-    let mut datasets = Datasets::new();
+    if !datasets.is_empty() {
+        io::print_info(format!("{} dataset(s) restored", datasets.len()));
+    }
+    batch::load_inputs(inputs, &mut datasets);
 
     loop {
+        // keep completions in sync with the datasets in memory
+        if let Some(helper) = rl.helper_mut() {
+            helper.update(&datasets);
+        }
+
         match rl.readline(&format!("\nRX {} ", io::PROMPT)) {
             Ok(line) => {
                 let trimmed = line.trim();
@@ -62,8 +155,8 @@ fn run_app() -> Result<(), Box<dyn error::Error>> {
                 }
 
                 match cmd::execute(trimmed, &mut datasets) {
-                    Ok(true) => {}
-                    Ok(false) => break,
+                    Ok(Status::Done | Status::Failed) => {}
+                    Ok(Status::Exit) => break,
                     Err(err) => io::print_error(format!("command error: {err}")),
                 }
 
@@ -82,18 +175,117 @@ fn run_app() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
-    // save history upon exit
-    rl.save_history(HISTORY_FILE)?;
+    // save history, and datasets if asked to, upon exit
+    rl.save_history(&history_file)?;
+    if config::get().repl.autosave == Some(true) && !datasets.is_empty() {
+        let path = Path::new(session::LAST_SESSION);
+        match session::save_session(path, &datasets) {
+            Ok(_) => io::print_info(format!("session saved to '{}'", path.display())),
+            Err(err) => io::print_error(format!("failed to save session: {err}")),
+        }
+    }
     Ok(())
 }
 
 /// Entry point of the application.
 fn main() {
     // parse command-line options (--help, --version, etc.)
-    let _matches = build_cli().get_matches();
+    let args: Vec<String> = env::args().collect();
+    let chain = parse_chain(&args).unwrap_or_else(|err| err.exit());
+
+    // read project and user settings
+    for problem in config::init() {
+        io::print_warn(problem);
+    }
+
+    let inputs: Vec<String> = chain
+        .iter()
+        .flat_map(|(_, m)| m.get_many::<String>("input").into_iter().flatten().cloned())
+        .collect();
+    let output = chain.iter().find_map(|(_, m)| m.get_one::<String>("output").cloned());
+    let command = chain[0].1.get_one::<String>("command").cloned();
+    let restore = chain[0].1.get_flag("restore");
+
+    let mut commands = Vec::new();
+    let mut script = None;
+    for (text, matches) in &chain {
+        match matches.subcommand() {
+            Some(("run", sub)) => script = sub.get_one::<String>("script").cloned(),
+            Some(_) => match cmd::Commands::from_arg_matches(matches) {
+                Ok(parsed) => commands.push((text.clone(), parsed)),
+                Err(err) => err.exit(),
+            },
+            None => {}
+        }
+    }
 
-    if let Err(err) = run_app() {
+    if script.is_some() && (chain.len() > 1 || command.is_some()) {
+        io::print_error("'run' cannot be combined with other commands");
+        std::process::exit(2);
+    }
+
+    if command.is_some() && !commands.is_empty() {
+        io::print_error("'--command' cannot be combined with subcommands");
+        std::process::exit(2);
+    }
+
+    // batch modes stop at the first failing command
+    let batch = script.is_some()
+        || command.is_some()
+        || !commands.is_empty()
+        || output.is_some()
+        || !stdin().is_terminal();
+
+    let mut datasets = Datasets::new();
+    if restore {
+        match session::load_session(Path::new(session::LAST_SESSION)) {
+            Ok((restored, compute)) => {
+                datasets = restored;
+                session::restore_settings(compute, false);
+            }
+            Err(err) => io::print_warn(format!("no session restored: {err}")),
+        }
+    }
+
+    if batch {
+        let succeeded = batch::load_inputs(&inputs, &mut datasets)
+            && match (&script, &command) {
+                (Some(script), _) => batch::run_script(script, &mut datasets),
+                (_, Some(command)) => batch::run_lines([command.clone()], "command", &mut datasets),
+                _ if !commands.is_empty() => batch::run_commands(commands, &mut datasets),
+                _ if !stdin().is_terminal() => batch::run_stdin(&mut datasets),
+                _ => true,
+            }
+            && output.as_ref().is_none_or(|out| batch::save_output(out, &mut datasets));
+        std::process::exit(if succeeded { 0 } else { 1 });
+    }
+
+    if let Err(err) = run_app(datasets, &inputs) {
         io::print_error(format!("fatal error: {err}"));
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(line: &str) -> Result<Vec<String>, clap::Error> {
+        let args: Vec<String> = shlex::split(&format!("cone {line}")).unwrap();
+        Ok(parse_chain(&args)?.into_iter().map(|(text, _)| text).collect())
+    }
+
+    #[test]
+    fn commands_are_chained_with_then() {
+        assert_eq!(chain("load -f a.csv then preview A").unwrap(), ["load -f a.csv", "preview A"]);
+        assert_eq!(chain("load -f preview").unwrap(), ["load -f preview"]);
+        assert_eq!(chain("rename A list").unwrap(), ["rename A list"]);
+        assert_eq!(chain("").unwrap(), [""]);
+    }
+
+    #[test]
+    fn then_needs_a_command() {
+        assert!(chain("load -f a.csv then").is_err());
+        assert!(chain("list then --output out").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use std::io::{BufRead, stdin};
+
+use crate::rx::Datasets;
+use crate::rx::cmd::{self, Commands, Status};
+use crate::rx::io;
+
+/// Executes a script of REPL commands, or standard input for `-`.
+///
+/// Returns whether every command succeeded.
+pub fn run_script(path: &str, datasets: &mut Datasets) -> bool {
+    if path == "-" {
+        return run_stdin(datasets);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(text) => run_lines(text.lines().map(String::from), path, datasets),
+        Err(err) => {
+            io::print_error(format!("failed to read '{path}': {err}"));
+            false
+        }
+    }
+}
+
+/// Executes the commands read from standard input, line by line.
+pub fn run_stdin(datasets: &mut Datasets) -> bool {
+    let lines = stdin().lock().lines().map_while(Result::ok);
+    run_lines(lines, "stdin", datasets)
+}
+
+/// Executes lines of commands in order, stopping at the first failing
+/// one or at `exit`.
+///
+/// Lines may hold several commands separated by `;`, which stop at the
+/// first failing one. Blank lines and lines starting with `#` are
+/// skipped, and failures are traced back to `origin` and their line.
+pub fn run_lines(
+    lines: impl IntoIterator<Item = String>,
+    origin: &str,
+    datasets: &mut Datasets,
+) -> bool {
+    for (number, line) in lines.into_iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        match status(cmd::execute(trimmed, datasets)) {
+            Status::Done => {}
+            Status::Exit => return true,
+            Status::Failed => {
+                io::print_error(format!("{origin}:{}: '{trimmed}' failed", number + 1));
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Executes commands parsed from the command line, stopping at the
+/// first failing one or at `exit`.
+///
+/// Each command is paired with its arguments, used in error messages.
+pub fn run_commands(commands: Vec<(String, Commands)>, datasets: &mut Datasets) -> bool {
+    for (text, command) in commands {
+        let result = cmd::dispatch_recorded(&text, command, datasets).map(|(status, _)| status);
+        match status(result) {
+            Status::Done => {}
+            Status::Exit => return true,
+            Status::Failed => {
+                io::print_error(format!("'{text}' failed"));
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Loads the given files as with `load --files`.
+pub fn load_inputs(files: &[String], datasets: &mut Datasets) -> bool {
+    if files.is_empty() {
+        return true;
+    }
+
+    let args = ["load", "--files"].into_iter().chain(files.iter().map(String::as_str));
+    run_joined(args, datasets)
+}
+
+/// Saves every dataset in memory to a file or directory, replacing
+/// existing files.
+pub fn save_output(output: &str, datasets: &mut Datasets) -> bool {
+    run_joined(["save", "*", "--output", output, "--overwrite"], datasets)
+}
+
+/// Quotes and executes a command given as separate arguments.
+fn run_joined<'a>(args: impl IntoIterator<Item = &'a str>, datasets: &mut Datasets) -> bool {
+    let Ok(line) = shlex::try_join(args) else {
+        io::print_error("arguments cannot contain null characters");
+        return false;
+    };
+
+    match status(cmd::execute(&line, datasets)) {
+        Status::Done | Status::Exit => true,
+        Status::Failed => {
+            io::print_error(format!("'{line}' failed"));
+            false
+        }
+    }
+}
+
+/// Returns the status of a command, reporting the errors it returned.
+fn status(result: Result<Status, clap::Error>) -> Status {
+    result.unwrap_or_else(|err| {
+        io::print_error(format!("command error: {err}"));
+        Status::Failed
+    })
+}
@@ -0,0 +1,169 @@
+use clap::Args;
+
+use crate::rx::cmd::{self, Status};
+use crate::rx::config::{self, Kind};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `alias` subcommand.
+#[derive(Args, Debug)]
+pub struct AliasCmd {
+    /// Name of the alias; every alias is listed when omitted.
+    #[arg(value_name = "ALIAS")]
+    name: Option<String>,
+
+    /// Command the alias stands for (quote it to include ';' or '|').
+    /// `$1`, `$2`, ... are replaced by the arguments typed after the
+    /// alias, which are otherwise appended to the command.
+    #[arg(value_name = "COMMAND", num_args = 1.., trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+
+    /// Remove the alias from the project settings.
+    #[arg(short, long, requires = "name", conflicts_with = "command")]
+    remove: bool,
+}
+
+/// Executes the `alias` command by listing, saving or removing aliases.
+pub fn run(cmd: AliasCmd, _datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(name) = cmd.name else {
+        print_definitions(Kind::Alias);
+        return Ok(Status::Done);
+    };
+
+    if cmd.remove {
+        return Ok(remove(Kind::Alias, &name));
+    }
+
+    if cmd.command.is_empty() {
+        let Some(command) = config::get().lookup(Kind::Alias, &name).map(String::from) else {
+            io::print_error(format!("alias '{name}' not found"));
+            return Ok(Status::Failed);
+        };
+        println!("{name} = {command}");
+        return Ok(Status::Done);
+    }
+
+    if let Err(err) = check_name(&name) {
+        io::print_error(err);
+        return Ok(Status::Failed);
+    }
+
+    let command = join(&cmd.command);
+    match config::define(Kind::Alias, &name, Some(&command)) {
+        Ok(path) => {
+            io::print_info(format!("alias '{name}' → '{}' saved", path.display()));
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(err);
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Replaces the aliases typed as commands in `line` by the commands they
+/// stand for, filling in their placeholders.
+///
+/// Arguments not referred to by a placeholder are appended. Expansions
+/// are not expanded again, so aliases cannot refer to one another.
+pub fn expand(line: &str) -> Result<String, String> {
+    let config = config::get();
+    if config.aliases.is_empty() {
+        return Ok(line.to_string());
+    }
+
+    let cli = cmd::repl_command();
+    let mut expanded = String::new();
+    let mut changed = false;
+
+    for (stage, separator) in cmd::split_stages(line) {
+        let tokens = shlex::split(stage).unwrap_or_default();
+        let definition = tokens
+            .first()
+            .filter(|name| cli.find_subcommand(name).is_none())
+            .and_then(|name| config.lookup(Kind::Alias, name));
+
+        match definition {
+            Some(definition) => {
+                let args = &tokens[1..];
+                let (mut text, used) = cmd::substitute(definition, args, None)
+                    .map_err(|err| format!("alias '{}': {err}", tokens[0]))?;
+                if let Ok(rest) = shlex::try_join(args[used.min(args.len())..].iter().map(String::as_str))
+                    && !rest.is_empty()
+                {
+                    text = format!("{text} {rest}");
+                }
+                expanded.push_str(&text);
+                changed = true;
+            }
+            None => expanded.push_str(stage.trim()),
+        }
+
+        if let Some(c) = separator {
+            expanded.push_str(&format!(" {c} "));
+        }
+    }
+
+    Ok(if changed { expanded } else { line.to_string() })
+}
+
+/// Checks that an alias or macro name is a single word that does not
+/// shadow a command.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!(
+            "invalid name '{name}' (use letters, digits, '_' and '-', starting with a letter)"
+        ));
+    }
+    if cmd::repl_command().find_subcommand(name).is_some() {
+        return Err(format!("'{name}' is already a command"));
+    }
+    Ok(())
+}
+
+/// Joins the words of a command, keeping a single quoted word as typed
+/// so it may hold several commands.
+pub fn join(words: &[String]) -> String {
+    match words {
+        [single] => single.trim().to_string(),
+        _ => shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" ")),
+    }
+}
+
+/// Prints the aliases or macros defined.
+pub fn print_definitions(kind: Kind) {
+    let config = config::get();
+    let definitions = config.definitions(kind);
+    if definitions.is_empty() {
+        io::print_warn(format!("no {} has been defined", kind.name()));
+        return;
+    }
+
+    let records = definitions
+        .iter()
+        .map(|(name, definition)| vec![name.clone(), definition.clone()])
+        .collect();
+    let headers = [kind.name(), "definition"].map(String::from).to_vec();
+    io::print_records(kind.table(), &format!("({} defined)", definitions.len()), headers, records);
+}
+
+/// Removes an alias or macro from the project settings.
+pub fn remove(kind: Kind, name: &str) -> Status {
+    if config::get().lookup(kind, name).is_none() {
+        io::print_error(format!("{} '{name}' not found", kind.name()));
+        return Status::Failed;
+    }
+
+    match config::define(kind, name, None) {
+        Ok(path) => {
+            io::print_info(format!("{} '{name}' removed from '{}'", kind.name(), path.display()));
+            Status::Done
+        }
+        Err(err) => {
+            io::print_error(err);
+            Status::Failed
+        }
+    }
+}
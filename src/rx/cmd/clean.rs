@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use clap::{Args, ValueEnum};
+use polars::prelude::*;
+
+use crate::rx::cmd::{parse_positive, select, Status};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Channels considered by default when clipping and removing spikes.
+const DEFAULT_CHANNELS: [&str; 3] = ["qc", "fs", "u2"];
+
+/// Replacement for negative cone and sleeve readings.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Clip {
+    /// Replace negative values with zero.
+    Zero,
+    /// Replace negative values with null.
+    Null,
+}
+
+/// Arguments for the `clean` subcommand.
+///
+/// Without rule flags, only the depth rules (null, duplicate and
+/// non-increasing depths) are applied.
+#[derive(Args, Debug)]
+pub struct CleanCmd {
+    /// Glob pattern of dataset names to clean.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Drop rows with null or NaN depth.
+    #[arg(long)]
+    null_depth: bool,
+
+    /// Drop rows repeating a previous depth.
+    #[arg(long)]
+    duplicates: bool,
+
+    /// Drop rows not deeper than every previous row.
+    #[arg(long)]
+    monotonic: bool,
+
+    /// Replace negative qc and fs readings.
+    #[arg(long, value_name = "WITH")]
+    clip: Option<Clip>,
+
+    /// Null readings beyond N standard deviations of their rolling window.
+    #[arg(long, value_name = "N", value_parser = parse_positive)]
+    spikes: Option<f64>,
+
+    /// Number of rows in the rolling window used to detect spikes.
+    #[arg(
+        long, value_name = "ROWS", default_value_t = 11,
+        value_parser = clap::value_parser!(u32).range(3..)
+    )]
+    window: u32,
+
+    /// Channels checked for spikes.
+    #[arg(long, value_name = "COLUMN", num_args = 1..)]
+    spike_columns: Vec<String>,
+
+    /// Drop rows shallower than the pre-drilled depth.
+    #[arg(long, value_name = "DEPTH")]
+    predrill: Option<f64>,
+}
+
+/// Executes the `clean` command by applying the selected rules to each
+/// matching dataset and reporting how many rows or values they affected.
+pub fn run(mut cmd: CleanCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let any_rule = cmd.null_depth
+        || cmd.duplicates
+        || cmd.monotonic
+        || cmd.clip.is_some()
+        || cmd.spikes.is_some()
+        || cmd.predrill.is_some();
+
+    if !any_rule {
+        cmd.null_depth = true;
+        cmd.duplicates = true;
+        cmd.monotonic = true;
+    }
+
+    let mut status = Status::Done;
+    for name in names {
+        let Some(dataset) = datasets.get_mut(&name) else {
+            continue;
+        };
+
+        let before = dataset.data.height();
+        match clean(&cmd, dataset) {
+            Ok(report) => {
+                let after = dataset.data.height();
+                let headers = ["rule", "affected"].map(String::from).to_vec();
+                let records = report
+                    .into_iter()
+                    .map(|(rule, count)| vec![rule, count.to_string()])
+                    .collect();
+                io::print_records(&name, &format!("({before} → {after} rows)"), headers, records);
+            }
+            Err(err) => {
+                io::print_error(format!("{name}: {err}"));
+                status = Status::Failed;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Applies the enabled rules in a fixed order and returns, for each one,
+/// the number of rows dropped or values replaced.
+fn clean(cmd: &CleanCmd, dataset: &mut Dataset) -> Result<Vec<(String, usize)>, String> {
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+    let mut df = dataset.data.clone();
+    let mut report = Vec::new();
+
+    if cmd.null_depth {
+        let mask = depth_mask(&df, &depth, |d| d.is_some_and(|d| !d.is_nan()))?;
+        report.push(("null depth rows dropped".to_string(), filter(&mut df, &mask)?));
+    }
+
+    if let Some(predrill) = cmd.predrill {
+        let mask = depth_mask(&df, &depth, |d| d.is_none_or(|d| d >= predrill))?;
+        report.push((format!("pre-drilled rows (< {predrill} m) dropped"), filter(&mut df, &mask)?));
+    }
+
+    if cmd.duplicates {
+        let mut seen = HashSet::new();
+        let mask = depth_mask(&df, &depth, |d| d.is_none_or(|d| seen.insert(d.to_bits())))?;
+        report.push(("duplicate depth rows dropped".to_string(), filter(&mut df, &mask)?));
+    }
+
+    if cmd.monotonic {
+        let mut deepest = f64::NEG_INFINITY;
+        let mask = depth_mask(&df, &depth, |d| match d {
+            Some(d) if d > deepest => {
+                deepest = d;
+                true
+            }
+            Some(_) => false,
+            None => true,
+        })?;
+        report.push(("non-increasing depth rows dropped".to_string(), filter(&mut df, &mask)?));
+    }
+
+    if let Some(clip) = cmd.clip {
+        for label in ["qc", "fs"] {
+            let Some(name) = dataset.find_column(&[label]) else {
+                continue;
+            };
+            let count = replace_values(&mut df, &name, |values| {
+                values
+                    .iter()
+                    .map(|v| match (v, clip) {
+                        (Some(x), Clip::Zero) if *x < 0.0 => Some(0.0),
+                        (Some(x), Clip::Null) if *x < 0.0 => None,
+                        _ => *v,
+                    })
+                    .collect()
+            })?;
+            report.push((format!("negative '{label}' values clipped"), count));
+        }
+    }
+
+    if let Some(n_std) = cmd.spikes {
+        let labels: Vec<&str> = if cmd.spike_columns.is_empty() {
+            DEFAULT_CHANNELS.to_vec()
+        } else {
+            cmd.spike_columns.iter().map(String::as_str).collect()
+        };
+
+        for label in labels {
+            let Some(name) = dataset.resolve_column(label) else {
+                if !cmd.spike_columns.is_empty() {
+                    return Err(format!("column '{label}' not found"));
+                }
+                continue;
+            };
+            let window = cmd.window as usize;
+            let count = replace_values(&mut df, &name, |values| despike(values, window, n_std))?;
+            report.push((format!("'{label}' spikes removed"), count));
+        }
+    }
+
+    dataset.data = df;
+    Ok(report)
+}
+
+/// Builds a row mask by calling `keep` with each depth value in order.
+fn depth_mask(
+    df: &DataFrame,
+    depth: &str,
+    keep: impl FnMut(Option<f64>) -> bool,
+) -> Result<BooleanChunked, String> {
+    let values = df
+        .column(depth)
+        .and_then(|c| c.cast(&DataType::Float64))
+        .map_err(|err| err.to_string())?;
+    let values = values.f64().map_err(|err| err.to_string())?;
+
+    Ok(values.into_iter().map(keep).collect())
+}
+
+/// Keeps the rows selected by `mask`, returning how many were dropped.
+fn filter(df: &mut DataFrame, mask: &BooleanChunked) -> Result<usize, String> {
+    let before = df.height();
+    *df = df.filter(mask).map_err(|err| err.to_string())?;
+    Ok(before - df.height())
+}
+
+/// Replaces the values of a column, returning how many changed.
+fn replace_values(
+    df: &mut DataFrame,
+    name: &str,
+    replace: impl FnOnce(&[Option<f64>]) -> Vec<Option<f64>>,
+) -> Result<usize, String> {
+    let column = df
+        .column(name)
+        .and_then(|c| c.cast(&DataType::Float64))
+        .map_err(|err| err.to_string())?;
+    let values: Vec<Option<f64>> = column
+        .f64()
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .collect();
+
+    let replaced = replace(&values);
+    let count = values
+        .iter()
+        .zip(&replaced)
+        .filter(|(old, new)| old.map(f64::to_bits) != new.map(f64::to_bits))
+        .count();
+
+    df.with_column(Column::new(name.into(), replaced))
+        .map_err(|err| err.to_string())?;
+    Ok(count)
+}
+
+/// Nulls values lying more than `n_std` standard deviations away from
+/// the mean of their centered rolling window (excluding themselves).
+fn despike(values: &[Option<f64>], window: usize, n_std: f64) -> Vec<Option<f64>> {
+    let half = window / 2;
+
+    (0..values.len())
+        .map(|i| {
+            let value = values[i]?;
+            let lo = i.saturating_sub(half);
+            let hi = (i + half + 1).min(values.len());
+
+            let neighbors: Vec<f64> = (lo..hi)
+                .filter(|&j| j != i)
+                .filter_map(|j| values[j])
+                .filter(|v| !v.is_nan())
+                .collect();
+
+            if neighbors.len() < 2 {
+                return Some(value);
+            }
+
+            let n = neighbors.len() as f64;
+            let mean = neighbors.iter().sum::<f64>() / n;
+            let var = neighbors.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+            if (value - mean).abs() > n_std * var.sqrt() {
+                None
+            } else {
+                Some(value)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,291 @@
+use clap::{Args, ValueEnum};
+use polars::prelude::*;
+
+use crate::rx::cmd::Status;
+use crate::rx::config::{self, Compute};
+use crate::rx::data::{WATER_UNIT_WEIGHT, split_column_name, stress_factor};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Properties that can be derived from the measured channels.
+///
+/// Variants are declared in dependency order.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Property {
+    /// Corrected cone resistance, qt = qc + u2·(1 − a).
+    Qt,
+    /// Hydrostatic pore pressure, u0 = γw·(z − zw).
+    U0,
+    /// Friction ratio, Rf = fs / qt · 100.
+    Rf,
+    /// Total vertical stress, σv0 = γ·z.
+    Sv0,
+    /// Effective vertical stress, σ'v0 = σv0 − u0.
+    Sv0Eff,
+    /// Net cone resistance, qn = qt − σv0.
+    Qn,
+    /// Undrained shear strength, su = qn / Nkt.
+    Su,
+}
+
+/// Formulas and references shown by `help compute`.
+const DETAILS: &str = "\
+Formulas:
+  qt       = qc + u2·(1 − a)          a: net area ratio
+  u0       = γw·(z − zw), for z > zw  γw = 9.81 kN/m³, zw: groundwater depth
+  Rf       = fs / qt · 100
+  sv0      = γ·z                      γ: soil unit weight, uniform with depth
+  sv0-eff  = sv0 − u0
+  qn       = qt − sv0
+  su       = qn / Nkt                 Nkt: cone factor, typically 10 to 20
+
+References:
+  ISO 22476-1:2022. Geotechnical investigation and testing — Field testing —
+    Part 1: Electrical cone and piezocone penetration test.
+  Lunne, T., Robertson, P.K. & Powell, J.J.M. (1997). Cone Penetration Testing
+    in Geotechnical Practice. Blackie Academic & Professional.
+  Robertson, P.K. & Cabal, K.L. (2022). Guide to Cone Penetration Testing for
+    Geotechnical Engineering, 7th ed. Gregg Drilling LLC.";
+
+/// Arguments for the `compute` subcommand.
+///
+/// Parameters not given default to the dataset metadata, then to the
+/// `compute` table of `cone.toml`.
+#[derive(Args, Debug)]
+#[command(after_long_help = DETAILS)]
+pub struct ComputeCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Properties to be computed.
+    #[arg(value_name = "PROPERTY", required = true, num_args = 1..)]
+    properties: Vec<Property>,
+
+    /// Net area ratio of the cone (overrides metadata).
+    #[arg(short, long, value_name = "RATIO")]
+    area_ratio: Option<f64>,
+
+    /// Groundwater table depth in meters (overrides metadata).
+    #[arg(short, long, value_name = "DEPTH")]
+    gwt: Option<f64>,
+
+    /// Soil unit weight in kN/m³.
+    #[arg(short = 'w', long, value_name = "WEIGHT")]
+    unit_weight: Option<f64>,
+
+    /// Cone factor for the undrained shear strength.
+    #[arg(short, long, value_name = "FACTOR")]
+    nkt: Option<f64>,
+}
+
+/// Executes the `compute` command by adding the requested properties
+/// as new columns of a dataset.
+pub fn run(mut cmd: ComputeCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get_mut(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    cmd.properties.sort();
+    cmd.properties.dedup();
+    let params = parameters(&cmd, dataset);
+
+    for property in &cmd.properties {
+        let result = match property {
+            Property::Qt => compute_qt(dataset, params.area_ratio),
+            Property::U0 => compute_u0(dataset, params.gwt),
+            Property::Rf => compute_rf(dataset),
+            Property::Sv0 => compute_sv0(dataset, params.unit_weight),
+            Property::Sv0Eff => compute_sv0_eff(dataset),
+            Property::Qn => compute_qn(dataset),
+            Property::Su => compute_su(dataset, params.nkt),
+        };
+
+        match result {
+            Ok(column) => io::print_info(format!("{} → '{}' computed", cmd.name, column)),
+            Err(err) => {
+                io::print_error(format!("{}: {err}", cmd.name));
+                return Ok(Status::Failed);
+            }
+        }
+    }
+
+    Ok(Status::Done)
+}
+
+/// Returns the parameters of a command, each taken from its options,
+/// then from the dataset metadata, then from `cone.toml`.
+fn parameters(cmd: &ComputeCmd, dataset: &Dataset) -> Compute {
+    let defaults = config::get().compute;
+    Compute {
+        area_ratio: cmd.area_ratio.or(dataset.meta.area_ratio).or(defaults.area_ratio),
+        gwt: cmd.gwt.or(dataset.meta.gwt).or(defaults.gwt),
+        unit_weight: cmd.unit_weight.or(defaults.unit_weight),
+        nkt: cmd.nkt.or(defaults.nkt),
+    }
+}
+
+/// Returns the command `text` with the parameters it would take from
+/// the metadata or `cone.toml` written as options, so the command
+/// recorded in provenance reproduces the result on its own.
+pub fn explicit(text: &str, cmd: &ComputeCmd, datasets: &Datasets) -> String {
+    let Some(dataset) = datasets.get(&cmd.name) else {
+        return text.to_string();
+    };
+    let params = parameters(cmd, dataset);
+    let options = [
+        (Property::Qt, "--area-ratio", cmd.area_ratio, params.area_ratio),
+        (Property::U0, "--gwt", cmd.gwt, params.gwt),
+        (Property::Sv0, "--unit-weight", cmd.unit_weight, params.unit_weight),
+        (Property::Su, "--nkt", cmd.nkt, params.nkt),
+    ];
+
+    let mut text = text.to_string();
+    for (property, option, given, value) in options {
+        if given.is_none()
+            && cmd.properties.contains(&property)
+            && let Some(value) = value
+        {
+            text.push_str(&format!(" {option}={value}"));
+        }
+    }
+    text
+}
+
+/// Adds the corrected cone resistance, in the units of qc.
+fn compute_qt(dataset: &mut Dataset, area_ratio: Option<f64>) -> Result<String, String> {
+    let area_ratio = area_ratio.ok_or("net area ratio is not set (use 'meta', '--area-ratio' or 'cone.toml')")?;
+
+    let qc = require_column(dataset, &["qc"])?;
+    let u2 = require_column(dataset, &["u2", "u"])?;
+    let (_, qc_unit) = split_column_name(&qc);
+    let factor = stress_ratio(&u2, &qc)?;
+
+    let name = format!("qt {qc_unit}").trim().to_string();
+    let expr = col(&qc) + col(&u2) * lit(factor * (1.0 - area_ratio));
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the hydrostatic pore pressure in kPa.
+fn compute_u0(dataset: &mut Dataset, gwt: Option<f64>) -> Result<String, String> {
+    let gwt = gwt.ok_or("groundwater depth is not set (use 'meta', '--gwt' or 'cone.toml')")?;
+
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+
+    let name = "u0 (kPa)".to_string();
+    let expr = when(col(&depth).gt(lit(gwt)))
+        .then((col(&depth) - lit(gwt)) * lit(WATER_UNIT_WEIGHT))
+        .otherwise(lit(0.0));
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the friction ratio in percent.
+fn compute_rf(dataset: &mut Dataset) -> Result<String, String> {
+    let fs = require_column(dataset, &["fs"])?;
+    let qt = dataset
+        .find_column(&["qt"])
+        .ok_or("'qt' must be computed first")?;
+    let factor = stress_ratio(&fs, &qt)?;
+
+    let name = "Rf (%)".to_string();
+    let expr = col(&fs) * lit(factor * 100.0) / col(&qt);
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the total vertical stress in kPa, for a uniform unit weight.
+fn compute_sv0(dataset: &mut Dataset, unit_weight: Option<f64>) -> Result<String, String> {
+    let unit_weight = unit_weight.ok_or("unit weight is not set (use '--unit-weight' or 'cone.toml')")?;
+    if unit_weight <= 0.0 {
+        return Err("the unit weight must be positive".into());
+    }
+
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+
+    let name = "sv0 (kPa)".to_string();
+    let expr = col(&depth) * lit(unit_weight);
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the effective vertical stress in kPa.
+fn compute_sv0_eff(dataset: &mut Dataset) -> Result<String, String> {
+    let sv0 = dataset
+        .find_column(&["sv0"])
+        .ok_or("'sv0' must be computed first")?;
+    let u0 = dataset
+        .find_column(&["u0"])
+        .ok_or("'u0' must be computed first")?;
+    let factor = stress_ratio(&u0, &sv0)?;
+
+    let name = "sv0_eff (kPa)".to_string();
+    let expr = col(&sv0) - col(&u0) * lit(factor);
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the net cone resistance, in the units of qt.
+fn compute_qn(dataset: &mut Dataset) -> Result<String, String> {
+    let qt = dataset
+        .find_column(&["qt"])
+        .ok_or("'qt' must be computed first")?;
+    let sv0 = dataset
+        .find_column(&["sv0"])
+        .ok_or("'sv0' must be computed first")?;
+    let (_, qt_unit) = split_column_name(&qt);
+    let factor = stress_ratio(&sv0, &qt)?;
+
+    let name = format!("qn {qt_unit}").trim().to_string();
+    let expr = col(&qt) - col(&sv0) * lit(factor);
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Adds the undrained shear strength in kPa.
+fn compute_su(dataset: &mut Dataset, nkt: Option<f64>) -> Result<String, String> {
+    let nkt = nkt.ok_or("cone factor is not set (use '--nkt' or 'cone.toml')")?;
+    if nkt <= 0.0 {
+        return Err("the cone factor must be positive".into());
+    }
+
+    let qn = dataset
+        .find_column(&["qn"])
+        .ok_or("'qn' must be computed first")?;
+
+    let name = "su (kPa)".to_string();
+    let expr = col(&qn) * lit(stress_ratio(&qn, &name)? / nkt);
+    add_column(dataset, expr.alias(&name))?;
+    Ok(name)
+}
+
+/// Looks up a column by label, failing with a readable message.
+fn require_column(dataset: &Dataset, labels: &[&str]) -> Result<String, String> {
+    dataset
+        .find_column(labels)
+        .ok_or_else(|| format!("no '{}' column found", labels[0]))
+}
+
+/// Returns the factor converting values of column `from` into the
+/// stress units of column `to`.
+fn stress_ratio(from: &str, to: &str) -> Result<f64, String> {
+    let unit_of = |name: &str| {
+        let (_, unit) = split_column_name(name);
+        stress_factor(&unit).ok_or(format!("unknown stress unit in '{name}'"))
+    };
+    Ok(unit_of(from)? / unit_of(to)?)
+}
+
+/// Evaluates `expr` over the dataset and stores the result as a column.
+fn add_column(dataset: &mut Dataset, expr: Expr) -> Result<(), String> {
+    dataset.data = dataset
+        .data
+        .clone()
+        .lazy()
+        .with_column(expr)
+        .collect()
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use clap::Args;
+use polars::prelude::*;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::data::Provenance;
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Arguments for the `concat` subcommand.
+///
+/// Columns missing from some datasets are filled with nulls. The new
+/// dataset starts with empty metadata, since soundings rarely share it.
+#[derive(Args, Debug)]
+pub struct ConcatCmd {
+    /// Glob pattern of dataset names to stack, in name order.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Name of the new dataset.
+    #[arg(long = "as", value_name = "NAME", required = true)]
+    output: String,
+
+    /// Name of the column holding the name of each row's dataset.
+    #[arg(short, long, value_name = "COLUMN", default_value = "Source")]
+    column: String,
+
+    /// Replace a dataset already using the output name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `concat` command by stacking the rows of the matching
+/// datasets into a new one.
+pub fn run(cmd: ConcatCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.len() < 2 {
+        io::print_error(format!(
+            "'{}' matches {} datasets, at least 2 are needed",
+            cmd.pattern,
+            names.len()
+        ));
+        return Ok(Status::Failed);
+    }
+
+    if datasets.contains_key(&cmd.output) && !cmd.overwrite {
+        io::print_error(format!(
+            "dataset '{}' already exists (use '--overwrite' to replace it)",
+            cmd.output
+        ));
+        return Ok(Status::Failed);
+    }
+
+    match concat(&cmd, &names, datasets) {
+        Ok(data) => {
+            let rows = data.height();
+            let mut stacked = Dataset::new(data);
+            stacked.provenance = Provenance::merge(names.iter().map(|name| &datasets[name].provenance));
+            datasets.insert(cmd.output.clone(), stacked);
+            io::print_info(format!(
+                "{} → '{}' stacked ({} datasets, {rows} rows)",
+                names.join(", "),
+                cmd.output,
+                names.len()
+            ));
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(err);
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Stacks the datasets, tagging each row with its dataset name.
+fn concat(cmd: &ConcatCmd, names: &[String], datasets: &Datasets) -> Result<DataFrame, String> {
+    let frames = names
+        .iter()
+        .map(|name| {
+            let df = &datasets[name].data;
+            if df.column(&cmd.column).is_ok() {
+                return Err(format!("{name}: column '{}' already exists (use '--column')", cmd.column));
+            }
+            let mut columns = vec![Column::new_scalar(
+                cmd.column.as_str().into(),
+                Scalar::from(PlSmallStr::from(name.as_str())),
+                df.height(),
+            )];
+            columns.extend(df.get_columns().iter().cloned());
+            let tagged = DataFrame::new(columns).map_err(|err| err.to_string())?;
+            Ok(tagged.lazy())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let args = UnionArgs { to_supertypes: true, ..Default::default() };
+    concat_lf_diagonal(frames, args)
+        .and_then(|lf| lf.collect())
+        .map_err(|err| err.to_string())
+}
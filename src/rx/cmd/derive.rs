@@ -0,0 +1,99 @@
+use clap::Args;
+use polars::prelude::*;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::expr::{self, Node};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Arguments for the `derive` subcommand.
+///
+/// The expression combines columns (by label, or quoted as in
+/// `'qc (MPa)'`), numbers, metadata (`meta.gwt`), the constants `pi`
+/// and `gamma_w`, the operators `+ - * / ^`, comparisons, `and`, `or`,
+/// `not` and functions such as `sqrt`, `ln`, `min`, `max` or `if`.
+#[derive(Args, Debug)]
+#[command(after_long_help = expr::reference())]
+pub struct DeriveCmd {
+    /// Glob pattern of dataset names.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Name of the column to be created, e.g. "su (kPa)".
+    #[arg(value_name = "COLUMN")]
+    target: String,
+
+    /// Assignment sign.
+    #[arg(value_name = "=", value_parser = ["="])]
+    assign: String,
+
+    /// Expression to be evaluated.
+    #[arg(
+        value_name = "EXPRESSION", required = true, num_args = 1..,
+        allow_negative_numbers = true
+    )]
+    expression: Vec<String>,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Replace a column already using the target name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `derive` command by evaluating an expression on each
+/// matching dataset and storing the result as a column.
+pub fn run(cmd: DeriveCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let tree = match expr::parse(&cmd.expression.join(" ")) {
+        Ok(tree) => tree,
+        Err(err) => {
+            io::print_error(format!("invalid expression: {err}"));
+            return Ok(Status::Failed);
+        }
+    };
+
+    let mut status = Status::Done;
+    for name in names {
+        let Some(dataset) = datasets.get_mut(&name) else {
+            continue;
+        };
+
+        match derive(&cmd, &tree, dataset) {
+            Ok(()) => io::print_info(format!("{name} → '{}' computed", cmd.target)),
+            Err(err) => {
+                io::print_error(format!("{name}: {err}"));
+                status = Status::Failed;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Evaluates the expression and adds its result to the dataset.
+fn derive(cmd: &DeriveCmd, tree: &Node, dataset: &mut Dataset) -> Result<(), String> {
+    if dataset.data.column(&cmd.target).is_ok() && !cmd.overwrite {
+        return Err(format!(
+            "column '{}' already exists (use '--overwrite' to replace it)",
+            cmd.target
+        ));
+    }
+
+    let expression = tree.compile(dataset)?;
+    dataset.data = dataset
+        .data
+        .clone()
+        .lazy()
+        .with_column(expression.alias(cmd.target.as_str()))
+        .collect()
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
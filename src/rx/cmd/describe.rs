@@ -0,0 +1,308 @@
+use clap::{ArgGroup, Args};
+use polars::prelude::*;
+
+use crate::rx::cmd::preview::parse_precision;
+use crate::rx::cmd::{select, Status};
+use crate::rx::data::split_column_name;
+use crate::rx::io::{self, Precision};
+use crate::rx::{Dataset, Datasets};
+
+/// Maximum number of groups accepted by `--by`.
+const MAX_GROUPS: usize = 50;
+
+/// Arguments for the `describe` subcommand.
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("grouping").args(["by", "layers"])))]
+pub struct DescribeCmd {
+    /// Glob pattern of dataset names to describe.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Columns to be described (all numeric columns by default).
+    #[arg(short, long, value_name = "COLUMN", num_args = 1..)]
+    columns: Vec<String>,
+
+    /// Column whose distinct values define groups (e.g. SBTn zone).
+    #[arg(long, value_name = "COLUMN")]
+    by: Option<String>,
+
+    /// Depths of the boundaries between layers.
+    #[arg(long, value_name = "DEPTH", num_args = 1..)]
+    layers: Vec<f64>,
+
+    /// Percentiles to be reported.
+    #[arg(
+        short = 'q', long, value_name = "P", num_args = 1..,
+        default_values_t = [10.0, 25.0, 75.0, 90.0],
+        value_parser = parse_percentile
+    )]
+    percentiles: Vec<f64>,
+
+    /// Decimal places, for all columns (N) or a single one (COLUMN=N).
+    #[arg(short, long, value_name = "[COLUMN=]N", value_parser = parse_precision)]
+    precision: Vec<(Option<String>, usize)>,
+}
+
+/// Executes the `describe` command by printing descriptive statistics
+/// of the numeric columns of each matching dataset.
+pub fn run(cmd: DescribeCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let precision = Precision::from_pairs(&cmd.precision);
+
+    let mut status = Status::Done;
+    for name in names {
+        let dataset = &datasets[&name];
+        if let Err(err) = describe(&cmd, &name, dataset, &precision) {
+            io::print_error(format!("{name}: {err}"));
+            status = Status::Failed;
+        }
+    }
+
+    Ok(status)
+}
+
+/// Prints one statistics table per group of a dataset.
+fn describe(
+    cmd: &DescribeCmd,
+    name: &str,
+    dataset: &Dataset,
+    precision: &Precision,
+) -> Result<(), String> {
+    let group_column = match &cmd.by {
+        Some(label) => Some(dataset.require_column(label)?),
+        None => None,
+    };
+
+    let columns: Vec<String> = if cmd.columns.is_empty() {
+        dataset
+            .data
+            .get_columns()
+            .iter()
+            .filter(|c| c.dtype().is_primitive_numeric())
+            .map(|c| c.name().to_string())
+            .filter(|c| Some(c) != group_column.as_ref())
+            .collect()
+    } else {
+        cmd.columns
+            .iter()
+            .map(|label| dataset.require_column(label))
+            .collect::<Result<_, _>>()?
+    };
+
+    if columns.is_empty() {
+        return Err("no numeric columns to describe".into());
+    }
+
+    let groups = if let Some(group_column) = &group_column {
+        groups_by_value(dataset, group_column)?
+    } else if !cmd.layers.is_empty() {
+        groups_by_layer(dataset, &cmd.layers)?
+    } else {
+        vec![(String::new(), lit(true))]
+    };
+
+    for (group, predicate) in groups {
+        let df = dataset
+            .data
+            .clone()
+            .lazy()
+            .filter(predicate)
+            .collect()
+            .map_err(|err| err.to_string())?;
+
+        let title = if group.is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} {} {group}", io::PROMPT)
+        };
+        print_statistics(&title, &df, &columns, &cmd.percentiles, precision)?;
+    }
+
+    Ok(())
+}
+
+/// Builds one filter per distinct value of `column`.
+fn groups_by_value(dataset: &Dataset, column: &str) -> Result<Vec<(String, Expr)>, String> {
+    let dtype = dataset.data.column(column).map_err(|err| err.to_string())?.dtype();
+    if !dtype.is_primitive_numeric() {
+        return Err(format!("column '{column}' is not numeric"));
+    }
+
+    // integer zones or layers are grouped like any other number
+    let values = dataset.read_column(column)?;
+    let mut distinct: Vec<f64> = values.into_iter().flatten().filter(|v| !v.is_nan()).collect();
+    distinct.sort_by(f64::total_cmp);
+    distinct.dedup();
+
+    if distinct.len() > MAX_GROUPS {
+        return Err(format!(
+            "column '{column}' has more than {MAX_GROUPS} distinct values to group by"
+        ));
+    }
+
+    let (label, _) = split_column_name(column);
+    Ok(distinct
+        .into_iter()
+        .map(|v| (format!("{label} = {v}"), col(column).cast(DataType::Float64).eq(lit(v))))
+        .collect())
+}
+
+/// Builds one filter per layer delimited by the given boundary depths.
+fn groups_by_layer(dataset: &Dataset, boundaries: &[f64]) -> Result<Vec<(String, Expr)>, String> {
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+    let (top, bottom) = dataset.depth_range().ok_or("depth column is empty")?;
+
+    let mut edges = vec![top];
+    let mut inner: Vec<f64> = boundaries
+        .iter()
+        .copied()
+        .filter(|d| *d > top && *d < bottom)
+        .collect();
+    inner.sort_by(f64::total_cmp);
+    edges.extend(inner);
+    edges.push(bottom);
+
+    let last = edges.len() - 2;
+    Ok(edges
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let (from, to) = (pair[0], pair[1]);
+            // the deepest layer includes its bottom boundary
+            let upper = if i == last {
+                col(&depth).lt_eq(lit(to))
+            } else {
+                col(&depth).lt(lit(to))
+            };
+            let predicate = col(&depth).gt_eq(lit(from)).and(upper);
+            (format!("layer {} ({from:.2} – {to:.2} m)", i + 1), predicate)
+        })
+        .collect())
+}
+
+/// Prints the statistics of the given columns, one per table column.
+fn print_statistics(
+    title: &str,
+    df: &DataFrame,
+    columns: &[String],
+    percentiles: &[f64],
+    precision: &Precision,
+) -> Result<(), String> {
+    let mut stat_names: Vec<String> = ["count", "nulls", "min", "max", "mean", "median", "std"]
+        .map(String::from)
+        .to_vec();
+    stat_names.extend(percentiles.iter().map(|p| format!("p{p}")));
+
+    let mut records: Vec<Vec<String>> = stat_names.into_iter().map(|s| vec![s]).collect();
+    let mut labels = vec!["statistic".to_string()];
+    let mut units = vec![String::new()];
+
+    for name in columns {
+        let column = df.column(name).map_err(|err| err.to_string())?;
+        let values = column
+            .cast(&DataType::Float64)
+            .map_err(|err| err.to_string())?;
+        let values = values.f64().map_err(|err| err.to_string())?;
+
+        let prec = precision.of(name);
+        let fmt = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{v:.prec$}"));
+
+        let mut valid: Vec<f64> = values.into_iter().flatten().filter(|v| !v.is_nan()).collect();
+        valid.sort_by(f64::total_cmp);
+        let nulls = values.len() - valid.len();
+
+        let mut cells = vec![
+            valid.len().to_string(),
+            nulls.to_string(),
+            fmt(valid.first().copied()),
+            fmt(valid.last().copied()),
+            fmt(mean(&valid)),
+            fmt(percentile(&valid, 50.0)),
+            fmt(std_dev(&valid)),
+        ];
+        cells.extend(percentiles.iter().map(|p| fmt(percentile(&valid, *p))));
+
+        for (record, cell) in records.iter_mut().zip(cells) {
+            record.push(cell);
+        }
+
+        let (label, unit) = split_column_name(name);
+        labels.push(label);
+        units.push(unit);
+    }
+
+    let plural = if df.height() == 1 { "row" } else { "rows" };
+    let subtitle = format!("(statistics of {} {plural})", df.height());
+    io::print_labeled_records(title, &subtitle, labels, units, records);
+    Ok(())
+}
+
+/// Arithmetic mean of the values.
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Sample standard deviation of the values.
+fn std_dev(values: &[f64]) -> Option<f64> {
+    let avg = mean(values)?;
+    if values.len() < 2 {
+        return None;
+    }
+    let sum_sq: f64 = values.iter().map(|v| (v - avg).powi(2)).sum();
+    Some((sum_sq / (values.len() - 1) as f64).sqrt())
+}
+
+/// Percentile of sorted values using linear interpolation.
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p / 100.0 * last as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64))
+}
+
+/// Ensures that the provided value is a percentile between 0 and 100.
+fn parse_percentile(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| "argument must be a number")?;
+    if (0.0..=100.0).contains(&val) {
+        Ok(val)
+    } else {
+        Err("percentiles must be between 0 and 100".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(dataset: &Dataset, predicate: Expr) -> usize {
+        dataset.data.clone().lazy().filter(predicate).collect().unwrap().height()
+    }
+
+    #[test]
+    fn groups_integer_columns() {
+        let data = df!("Depth (m)" => [0.1, 0.2, 0.3, 0.4], "zone" => [2i64, 1, 2, 2]).unwrap();
+        let dataset = Dataset::new(data);
+
+        let groups = groups_by_value(&dataset, "zone").unwrap();
+        let labels: Vec<&str> = groups.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, ["zone = 1", "zone = 2"]);
+        assert_eq!(rows(&dataset, groups[0].1.clone()), 1);
+        assert_eq!(rows(&dataset, groups[1].1.clone()), 3);
+    }
+
+    #[test]
+    fn rejects_text_columns() {
+        let data = df!("Depth (m)" => [0.1, 0.2], "unit" => ["clay", "sand"]).unwrap();
+        let err = groups_by_value(&Dataset::new(data), "unit").unwrap_err();
+        assert_eq!(err, "column 'unit' is not numeric");
+    }
+}
@@ -0,0 +1,33 @@
+use clap::Args;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `drop` subcommand.
+#[derive(Args, Debug)]
+pub struct DropCmd {
+    /// Glob pattern of dataset names to remove.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+}
+
+/// Executes the `drop` command by removing matching datasets from memory.
+pub fn run(cmd: DropCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    for name in names {
+        datasets.remove(&name);
+        io::print_info(format!("'{name}' dropped"));
+    }
+
+    Ok(Status::Done)
+}
@@ -0,0 +1,104 @@
+use clap::Args;
+use polars::prelude::*;
+
+use crate::rx::cmd::Status;
+use crate::rx::expr;
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Arguments for the `filter` subcommand.
+///
+/// The condition uses the syntax of `derive` expressions, for instance
+/// `depth > 3 and Ic < 2.6`. Rows where it is false or missing are
+/// left out.
+#[derive(Args, Debug)]
+#[command(after_long_help = expr::reference())]
+pub struct FilterCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Condition the kept rows must satisfy.
+    #[arg(
+        value_name = "CONDITION", required = true, num_args = 1..,
+        allow_negative_numbers = true
+    )]
+    condition: Vec<String>,
+
+    /// Name of the new dataset.
+    #[arg(long = "as", value_name = "NAME", required = true)]
+    output: String,
+
+    /// Replace a dataset already using the output name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `filter` command by storing the rows of a dataset that
+/// satisfy a condition as a new dataset.
+pub fn run(cmd: FilterCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    if cmd.output == cmd.name {
+        io::print_error("the filtered dataset needs a name of its own");
+        return Ok(Status::Failed);
+    }
+
+    if datasets.contains_key(&cmd.output) && !cmd.overwrite {
+        io::print_error(format!(
+            "dataset '{}' already exists (use '--overwrite' to replace it)",
+            cmd.output
+        ));
+        return Ok(Status::Failed);
+    }
+
+    let condition = cmd.condition.join(" ");
+    match filter(dataset, &condition) {
+        Ok(data) => {
+            let (before, after) = (dataset.data.height(), data.height());
+            let filtered = Dataset {
+                data,
+                meta: dataset.meta.clone(),
+                provenance: dataset.provenance.clone(),
+            };
+            datasets.insert(cmd.output.clone(), filtered);
+            io::print_info(format!(
+                "{} → '{}' filtered ({before} → {after} rows)",
+                cmd.name, cmd.output
+            ));
+            if after == 0 {
+                io::print_warn(format!("no rows of '{}' satisfy the condition", cmd.name));
+            }
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(format!("{}: {err}", cmd.name));
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Returns the rows of the dataset satisfying the condition.
+fn filter(dataset: &Dataset, condition: &str) -> Result<DataFrame, String> {
+    let tree = expr::parse(condition).map_err(|err| format!("invalid condition: {err}"))?;
+    let predicate = tree.compile(dataset)?;
+    let frame = dataset.data.clone().lazy();
+
+    // checked beforehand, as polars reports it along with the query plan
+    let schema = frame
+        .clone()
+        .select([predicate.clone()])
+        .collect_schema()
+        .map_err(|err| err.to_string())?;
+    if schema.iter_values().any(|dtype| *dtype != DataType::Boolean) {
+        return Err("the condition must compare values (e.g. 'qc > 2')".into());
+    }
+
+    frame
+        .filter(predicate)
+        .collect()
+        .map_err(|err| err.to_string())
+}
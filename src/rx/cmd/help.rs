@@ -0,0 +1,47 @@
+use clap::{Args, CommandFactory};
+
+use crate::rx::cmd::{ReplCli, Status};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `help` subcommand.
+#[derive(Args, Debug)]
+pub struct HelpCmd {
+    /// Command to be described.
+    #[arg(value_name = "COMMAND")]
+    command: Option<String>,
+}
+
+/// Executes the `help` command by listing every command or describing
+/// the usage, arguments, formulas and references of one.
+pub fn run(cmd: HelpCmd, _datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let cli = ReplCli::command();
+
+    let Some(name) = cmd.command else {
+        let records: Vec<Vec<String>> = cli
+            .get_subcommands()
+            .map(|sub| {
+                let about = sub.get_about().map(|s| s.to_string()).unwrap_or_default();
+                vec![sub.get_name().to_string(), about]
+            })
+            .collect();
+
+        let headers = ["command", "description"].map(String::from).to_vec();
+        let subtitle = format!("({} commands, 'help COMMAND' for details)", records.len());
+        io::print_records("commands", &subtitle, headers, records);
+        return Ok(Status::Done);
+    };
+
+    // described on its own, as the REPL has no binary name to prefix usage with
+    match cli.find_subcommand(name.to_ascii_lowercase()) {
+        Some(sub) => {
+            let mut sub = sub.clone().disable_help_flag(true).disable_help_subcommand(true);
+            println!("{}", sub.render_long_help().ansi());
+            Ok(Status::Done)
+        }
+        None => {
+            io::print_error(format!("unknown command '{name}' (type 'help' to list them)"));
+            Ok(Status::Failed)
+        }
+    }
+}
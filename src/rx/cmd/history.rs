@@ -0,0 +1,38 @@
+use clap::Args;
+
+use crate::rx::cmd::Status;
+use crate::rx::history::{self, State, HISTORY_LIMIT};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `history` subcommand.
+#[derive(Args, Debug)]
+pub struct HistoryCmd {}
+
+/// Executes the `history` command by listing the operations that can be
+/// undone or redone.
+pub fn run(_cmd: HistoryCmd, _datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let entries = history::entries();
+    if entries.is_empty() {
+        io::print_warn("no operations have been recorded");
+        return Ok(Status::Done);
+    }
+
+    let records = entries
+        .iter()
+        .enumerate()
+        .map(|(i, (label, state))| {
+            let state = match state {
+                State::Done => "done",
+                State::Undone => "undone",
+            };
+            vec![(i + 1).to_string(), label.clone(), state.to_string()]
+        })
+        .collect();
+
+    let headers = ["#", "operation", "state"].map(String::from).to_vec();
+    let subtitle = format!("({} of at most {HISTORY_LIMIT} operations)", entries.len());
+    io::print_records("history", &subtitle, headers, records);
+
+    Ok(Status::Done)
+}
@@ -0,0 +1,221 @@
+use clap::{ArgGroup, Args};
+use polars::prelude::*;
+
+use crate::rx::cmd::Status;
+use crate::rx::data::split_column_name;
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Labels recognized as the resultant inclination channel.
+const INCLINATION_LABELS: [&str; 3] = ["inclination", "incl", "i"];
+
+/// Labels recognized as the inclination channel along the x axis.
+const INCLINATION_X_LABELS: [&str; 3] = ["ix", "incl x", "inclination x"];
+
+/// Labels recognized as the inclination channel along the y axis.
+const INCLINATION_Y_LABELS: [&str; 3] = ["iy", "incl y", "inclination y"];
+
+/// Name of the column holding true vertical depth when it is added.
+const TVD_COLUMN: &str = "TVD (m)";
+
+/// Name given to the original depth column when it is replaced.
+const LENGTH_COLUMN: &str = "Length (m)";
+
+/// Formulas and references shown by `help incline`.
+const DETAILS: &str = "\
+Formulas:
+  i  = atan(√(tan²ix + tan²iy))         resultant of dual-axis readings
+  Δz = Δl·cos((i₁ + i₂) / 2)            per increment of penetration length
+
+References:
+  ISO 22476-1:2022. Geotechnical investigation and testing — Field testing —
+    Part 1: Electrical cone and piezocone penetration test.
+  Lunne, T., Robertson, P.K. & Powell, J.J.M. (1997). Cone Penetration Testing
+    in Geotechnical Practice. Blackie Academic & Professional.";
+
+/// Arguments for the `incline` subcommand.
+///
+/// Without column options, the inclination channels are detected by
+/// their labels, preferring dual-axis readings.
+#[derive(Args, Debug)]
+#[command(
+    group(ArgGroup::new("channels").args(["column", "x"])),
+    after_long_help = DETAILS
+)]
+pub struct InclineCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Column of the resultant inclination from vertical, in degrees.
+    #[arg(short, long, value_name = "COLUMN")]
+    column: Option<String>,
+
+    /// Column of the inclination along the x axis, in degrees.
+    #[arg(short, long, value_name = "COLUMN", requires = "y")]
+    x: Option<String>,
+
+    /// Column of the inclination along the y axis, in degrees.
+    #[arg(short, long, value_name = "COLUMN", requires = "x")]
+    y: Option<String>,
+
+    /// Replace the depth column, keeping penetration length apart.
+    ///
+    /// Once replaced, true vertical depth is computed again from the
+    /// kept penetration length.
+    #[arg(short, long)]
+    replace: bool,
+
+    /// Inclination above which a warning is issued, in degrees.
+    #[arg(short, long, value_name = "DEGREES", default_value_t = 5.0)]
+    warn: f64,
+}
+
+/// Executes the `incline` command by computing true vertical depth from
+/// penetration length and inclination readings.
+pub fn run(cmd: InclineCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get_mut(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    if let Err(err) = incline(&cmd, dataset) {
+        io::print_error(format!("{}: {err}", cmd.name));
+        return Ok(Status::Failed);
+    }
+
+    Ok(Status::Done)
+}
+
+/// Computes and stores true vertical depth, reporting steep readings.
+fn incline(cmd: &InclineCmd, dataset: &mut Dataset) -> Result<(), String> {
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+    let (_, unit) = split_column_name(&depth);
+    let depth_unit = unit.trim_start_matches('(').trim_end_matches(')');
+
+    // penetration length kept by an earlier `--replace`, as depth is then
+    // already vertical
+    let kept = dataset.data.column(LENGTH_COLUMN).is_ok();
+    let source = if kept { LENGTH_COLUMN } else { depth.as_str() };
+    let lengths = dataset.read_column(source)?;
+    let inclinations = inclination(cmd, dataset)?;
+
+    // exceedances of the warning threshold
+    let steep: Vec<usize> = inclinations
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| i.is_some_and(|i| i > cmd.warn))
+        .map(|(k, _)| k)
+        .collect();
+
+    if let Some(&first) = steep.first() {
+        let max = inclinations.iter().flatten().copied().fold(f64::NAN, f64::max);
+        io::print_warn(format!(
+            "{}: inclination exceeds {}° in {} rows (from {:.2} {depth_unit}, max {max:.2}°)",
+            cmd.name,
+            cmd.warn,
+            steep.len(),
+            lengths[first].unwrap_or_default(),
+        ));
+    }
+
+    let tvd = vertical_depth(&lengths, &inclinations);
+    let offset = lengths
+        .iter()
+        .zip(&tvd)
+        .filter_map(|(l, z)| Some(l.as_ref()? - z.as_ref()?))
+        .next_back()
+        .unwrap_or_default();
+
+    let df = &mut dataset.data;
+    if cmd.replace {
+        if !kept {
+            let length = df
+                .column(&depth)
+                .map_err(|err| err.to_string())?
+                .clone()
+                .with_name(LENGTH_COLUMN.into());
+            df.with_column(length).map_err(|err| err.to_string())?;
+        }
+        df.with_column(Column::new(depth.as_str().into(), tvd))
+            .map_err(|err| err.to_string())?;
+        io::print_info(format!("{} → '{depth}' replaced by true vertical depth", cmd.name));
+    } else {
+        df.with_column(Column::new(TVD_COLUMN.into(), tvd))
+            .map_err(|err| err.to_string())?;
+        io::print_info(format!("{} → '{TVD_COLUMN}' computed", cmd.name));
+    }
+
+    io::print_info(format!(
+        "{}: depth reduced by {offset:.3} {depth_unit} at the end of the sounding",
+        cmd.name
+    ));
+    Ok(())
+}
+
+/// Returns the resultant inclination of each row, in degrees.
+///
+/// Dual-axis readings are combined as atan(√(tan²ix + tan²iy)).
+fn inclination(cmd: &InclineCmd, dataset: &Dataset) -> Result<Vec<Option<f64>>, String> {
+    let (x, y) = match (&cmd.x, &cmd.y) {
+        (Some(x), Some(y)) => (Some(dataset.require_column(x)?), Some(dataset.require_column(y)?)),
+        _ if cmd.column.is_none() => (
+            dataset.find_column(&INCLINATION_X_LABELS),
+            dataset.find_column(&INCLINATION_Y_LABELS),
+        ),
+        _ => (None, None),
+    };
+
+    if let (Some(x), Some(y)) = (x, y) {
+        let ix = dataset.read_column(&x)?;
+        let iy = dataset.read_column(&y)?;
+        return Ok(ix
+            .into_iter()
+            .zip(iy)
+            .map(|(ix, iy)| {
+                let (tx, ty) = (ix?.to_radians().tan(), iy?.to_radians().tan());
+                Some(tx.hypot(ty).atan().to_degrees())
+            })
+            .collect());
+    }
+
+    let single = match &cmd.column {
+        Some(label) => dataset.require_column(label)?,
+        None => dataset
+            .find_column(&INCLINATION_LABELS)
+            .ok_or("no inclination column found (use '--column' or '--x/--y')")?,
+    };
+    dataset.read_column(&single)
+}
+
+/// Integrates penetration length increments projected on the vertical.
+///
+/// Each increment uses the mean inclination of its two ends; missing
+/// readings repeat the last known inclination.
+fn vertical_depth(lengths: &[Option<f64>], inclinations: &[Option<f64>]) -> Vec<Option<f64>> {
+    let mut tvd = Vec::with_capacity(lengths.len());
+    let mut last: Option<(f64, f64, f64)> = None; // (length, inclination, depth)
+    let mut known = 0.0;
+
+    for (length, incl) in lengths.iter().zip(inclinations) {
+        known = incl.filter(|i| !i.is_nan()).unwrap_or(known);
+
+        let Some(length) = length.filter(|l| !l.is_nan()) else {
+            tvd.push(None);
+            continue;
+        };
+
+        let depth = match last {
+            Some((prev_length, prev_incl, prev_depth)) => {
+                let mean = ((prev_incl + known) / 2.0).to_radians();
+                prev_depth + (length - prev_length) * mean.cos()
+            }
+            None => length * known.to_radians().cos(),
+        };
+
+        last = Some((length, known, depth));
+        tvd.push(Some(depth));
+    }
+
+    tvd
+}
@@ -0,0 +1,67 @@
+use clap::Args;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::data::split_column_name;
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Arguments for the `info` subcommand.
+#[derive(Args, Debug)]
+pub struct InfoCmd {
+    /// Glob pattern of dataset names to describe.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+}
+
+/// Executes the `info` command by printing the schema of each
+/// matching dataset.
+pub fn run(cmd: InfoCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    for name in names {
+        let dataset = &datasets[&name];
+        let (height, width) = dataset.data.shape();
+        let subtitle = format!("({height} rows × {width} columns)");
+
+        let headers = ["column", "unit", "type", "values", "nulls", "NaN"]
+            .map(String::from)
+            .to_vec();
+        io::print_records(&name, &subtitle, headers, schema_records(dataset));
+    }
+
+    Ok(Status::Done)
+}
+
+/// Builds one record per column with its unit, type and missing counts.
+fn schema_records(dataset: &Dataset) -> Vec<Vec<String>> {
+    dataset
+        .data
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let (label, unit) = split_column_name(column.name());
+            let nulls = column.null_count();
+            let nans = dataset
+                .values(column.name())
+                .map(|values| values.into_iter().flatten().filter(|v| v.is_nan()).count())
+                .unwrap_or(0);
+
+            vec![
+                label,
+                unit,
+                column.dtype().to_string(),
+                (column.len() - nulls - nans).to_string(),
+                nulls.to_string(),
+                nans.to_string(),
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,188 @@
+use clap::{Args, ValueEnum};
+use polars::prelude::*;
+
+use crate::rx::cmd::Status;
+use crate::rx::data::{Provenance, split_column_name};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Direction in which a matching depth is searched for.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    /// Closest depth above or below.
+    Nearest,
+    /// Closest depth not below the row.
+    Backward,
+    /// Closest depth not above the row.
+    Forward,
+}
+
+/// Arguments for the `join` subcommand.
+///
+/// Every row of the left dataset is kept and matched with the right row
+/// closest in depth (an as-of join). Right columns whose names clash
+/// with left ones, including its depth, get a suffix.
+#[derive(Args, Debug)]
+pub struct JoinCmd {
+    /// Name of the dataset whose rows are kept.
+    #[arg(value_name = "LEFT")]
+    left: String,
+
+    /// Name of the dataset whose columns are added.
+    #[arg(value_name = "RIGHT")]
+    right: String,
+
+    /// Name of the new dataset.
+    #[arg(long = "as", value_name = "NAME", required = true)]
+    output: String,
+
+    /// Largest depth difference accepted as a match, in meters.
+    #[arg(short, long, value_name = "DZ")]
+    tolerance: Option<f64>,
+
+    /// Direction in which matches are searched for.
+    #[arg(short, long, value_name = "STRATEGY", default_value = "nearest")]
+    strategy: Strategy,
+
+    /// Suffix for clashing right columns (defaults to '_' and its name).
+    #[arg(long, value_name = "SUFFIX")]
+    suffix: Option<String>,
+
+    /// Replace a dataset already using the output name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `join` command by merging two datasets on depth.
+pub fn run(cmd: JoinCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    for name in [&cmd.left, &cmd.right] {
+        if !datasets.contains_key(name) {
+            io::print_error(format!("dataset '{name}' not found"));
+            return Ok(Status::Failed);
+        }
+    }
+
+    if datasets.contains_key(&cmd.output) && !cmd.overwrite {
+        io::print_error(format!(
+            "dataset '{}' already exists (use '--overwrite' to replace it)",
+            cmd.output
+        ));
+        return Ok(Status::Failed);
+    }
+
+    if cmd.tolerance.is_some_and(|t| t < 0.0) {
+        io::print_error("the tolerance must not be negative");
+        return Ok(Status::Failed);
+    }
+
+    let (left, right) = (&datasets[&cmd.left], &datasets[&cmd.right]);
+    match join(&cmd, left, right) {
+        Ok((data, matched)) => {
+            let rows = data.height();
+            let provenance = Provenance::merge([&left.provenance, &right.provenance]);
+            let joined = Dataset { data, meta: left.meta.clone(), provenance };
+            datasets.insert(cmd.output.clone(), joined);
+            io::print_info(format!(
+                "{} + {} → '{}' joined ({matched} of {rows} rows matched)",
+                cmd.left, cmd.right, cmd.output
+            ));
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(format!("{} + {}: {err}", cmd.left, cmd.right));
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Appends the columns of `right` to `left`, returning the merged data
+/// and the number of rows with a match.
+fn join(cmd: &JoinCmd, left: &Dataset, right: &Dataset) -> Result<(DataFrame, usize), String> {
+    let left_depths = depths(left, "left")?;
+    let right_depths = depths(right, "right")?;
+
+    let increasing = right_depths
+        .iter()
+        .flatten()
+        .zip(right_depths.iter().flatten().skip(1))
+        .all(|(a, b)| a < b);
+    if !increasing {
+        return Err("right depth must increase monotonically (run 'clean' first)".into());
+    }
+
+    // right samples as (depth, row), skipping rows without depth
+    let samples: Vec<(f64, IdxSize)> = right_depths
+        .iter()
+        .enumerate()
+        .filter_map(|(i, z)| Some((z.filter(|z| !z.is_nan())?, i as IdxSize)))
+        .collect();
+
+    let indices: IdxCa = left_depths
+        .iter()
+        .map(|z| {
+            let z = z.filter(|z| !z.is_nan())?;
+            let (depth, row) = closest(&samples, z, cmd.strategy)?;
+            cmd.tolerance
+                .is_none_or(|t| (depth - z).abs() <= t + 1e-9)
+                .then_some(row)
+        })
+        .collect();
+    let matched = indices.len() - indices.null_count();
+
+    let suffix = cmd.suffix.clone().unwrap_or_else(|| format!("_{}", cmd.right));
+    let mut data = left.data.clone();
+
+    for column in right.data.get_columns() {
+        let name = column.name().to_string();
+        let target = if data.column(&name).is_ok() {
+            let (label, unit) = split_column_name(&name);
+            format!("{label}{suffix} {unit}").trim_end().to_string()
+        } else {
+            name
+        };
+        if data.column(&target).is_ok() {
+            return Err(format!("column '{target}' already exists (use '--suffix')"));
+        }
+
+        let values = column
+            .as_materialized_series()
+            .take(&indices)
+            .map_err(|err| err.to_string())?
+            .with_name(target.as_str().into());
+        data.with_column(values).map_err(|err| err.to_string())?;
+    }
+
+    Ok((data, matched))
+}
+
+/// Returns the depth values of a dataset.
+fn depths(dataset: &Dataset, side: &str) -> Result<Vec<Option<f64>>, String> {
+    let depth = dataset
+        .depth_column()
+        .ok_or(format!("{side} dataset has no depth column"))?;
+    Ok(dataset
+        .values(&depth)
+        .ok_or(format!("{side} depth is not numeric"))?
+        .into_iter()
+        .collect())
+}
+
+/// Finds the sample closest to depth `z` in the given direction.
+fn closest(samples: &[(f64, IdxSize)], z: f64, strategy: Strategy) -> Option<(f64, IdxSize)> {
+    let i = samples.partition_point(|(zi, _)| *zi < z);
+    let above = i.checked_sub(1).and_then(|j| samples.get(j));
+    let below = samples.get(i);
+
+    match strategy {
+        Strategy::Backward => match below {
+            Some(s) if s.0 == z => Some(*s),
+            _ => above.copied(),
+        },
+        Strategy::Forward => below.copied(),
+        Strategy::Nearest => [above, below]
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| (a.0 - z).abs().total_cmp(&(b.0 - z).abs()))
+            .copied(),
+    }
+}
@@ -0,0 +1,51 @@
+use clap::Args;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `list` subcommand.
+#[derive(Args, Debug)]
+pub struct ListCmd {
+    /// Glob pattern of dataset names to list.
+    #[arg(value_name = "PATTERN", default_value = "*")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+}
+
+/// Executes the `list` command by summarizing each matching dataset.
+pub fn run(cmd: ListCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    if datasets.is_empty() {
+        io::print_warn("no datasets have been loaded");
+        return Ok(Status::Done);
+    }
+
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let records = names
+        .iter()
+        .map(|name| {
+            let dataset = &datasets[name];
+            let depth = dataset
+                .depth_range()
+                .map(|(min, max)| format!("{min:.2} – {max:.2}"))
+                .unwrap_or_else(|| "-".into());
+            let source = dataset.meta.get("source").unwrap_or_else(|| "-".into());
+
+            vec![name.clone(), dataset.data.height().to_string(), depth, source]
+        })
+        .collect();
+
+    let headers = ["name", "rows", "depth (m)", "source"].map(String::from).to_vec();
+    let plural = if names.len() == 1 { "dataset" } else { "datasets" };
+    io::print_records("datasets", &format!("({} {plural})", names.len()), headers, records);
+
+    Ok(Status::Done)
+}
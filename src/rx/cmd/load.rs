@@ -1,12 +1,17 @@
 use std::fs;
 use std::error::Error;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 use clap::{ArgGroup, Args};
 use polars::prelude::*;
+use regex::Regex;
 
+use crate::rx::cmd::Status;
+use crate::rx::config;
+use crate::rx::data::{Metadata, Provenance};
 use crate::rx::io;
-use crate::rx::Datasets;
+use crate::rx::{Dataset, Datasets};
 
 /// Arguments for the `load` subcommand.
 #[derive(Args, Debug)]
@@ -23,37 +28,105 @@ pub struct LoadCmd {
     /// Path of directory to load.
     #[arg(short, long, value_name = "DIR")]
     dir: Option<PathBuf>,
+
+    /// Name of the dataset (single file only), kept as typed like the
+    /// names given to `rename`.
+    #[arg(long = "as", value_name = "NAME", conflicts_with_all = ["strip", "regex", "template"])]
+    name: Option<String>,
+
+    /// Prefix removed from file stems before naming.
+    #[arg(long, value_name = "PREFIX")]
+    strip: Option<String>,
+
+    /// Regex applied to file stems; its first capture group is the name.
+    #[arg(long, value_name = "REGEX", value_parser = parse_regex)]
+    regex: Option<Regex>,
+
+    /// Template for dataset names, where `{}` stands for the derived name.
+    #[arg(long, value_name = "TEMPLATE")]
+    template: Option<String>,
+
+    /// Replace datasets that already exist in memory.
+    #[arg(long)]
+    overwrite: bool,
 }
 
 /// Executes the `load` command by validating and importing a file or directory.
-pub fn run(cmd: LoadCmd, datasets: &mut Datasets) -> Result<bool, clap::Error> {
-    let mut loaded_files = Vec::new();
-
-    // validate and collect valid files from `-f`
-    for path in &cmd.files {
-        if let Some((name, file_path)) = validate_file_path(path, datasets) {
-            match read_csv(&file_path) {
-                Ok(df) => {
-                    datasets.insert(name.clone(), df);
-                    loaded_files.push((name, file_path));
-                }
-                Err(_) => {
-                    io::print_error(format!(
-                        "failed to load file '{}'", file_path.display(),
-                    ));
-                }
-            }
+pub fn run(cmd: LoadCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let mut paths: Vec<PathBuf> = cmd
+        .files
+        .iter()
+        .filter(|path| validate_file_path(path))
+        .cloned()
+        .collect();
+    let mut status = if paths.len() < cmd.files.len() { Status::Failed } else { Status::Done };
+
+    // validate and collect valid files from `-d`
+    if let Some(dir_path) = &cmd.dir {
+        let mut files_paths = validate_dir_path(dir_path);
+        if files_paths.is_empty() {
+            status = Status::Failed;
         }
+        files_paths.sort();
+        paths.extend(files_paths);
     }
 
-    //TODO: IMPROVE AUTOCOMPLETEEEE to load many files using -f
+    if cmd.name.is_some() && paths.len() > 1 {
+        io::print_error("'--as' can only be used when loading a single file");
+        return Ok(Status::Failed);
+    }
 
-    // validate and collect valid files from `-d`
-    if let Some(dir_path) = &cmd.dir {
-        let files_paths = validate_dir_path(dir_path);
-        if !files_paths.is_empty() {
+    let mut loaded_files: Vec<(String, PathBuf)> = Vec::new();
+
+    for file_path in paths {
+        let Some(name) = dataset_name(&cmd, &file_path) else {
+            status = Status::Failed;
+            continue;
+        };
+
+        if loaded_files.iter().any(|(loaded, _)| *loaded == name) {
+            io::print_error(format!(
+                "\"{}\" (from '{}') clashes with another file in this load",
+                name,
+                file_path.display()
+            ));
+            status = Status::Failed;
+            continue;
+        }
+
+        if datasets.contains_key(&name) && !cmd.overwrite {
+            io::print_error(format!(
+                "\"{}\" (from '{}') already exists in memory",
+                name,
+                file_path.display()
+            ));
+            io::print_error("use '--overwrite', '--as', 'rename' or 'drop' to free the name");
+            status = Status::Failed;
+            continue;
+        }
+
+        match read_csv(&file_path) {
+            Ok(mut df) => {
+                map_columns(&mut df, &file_path);
+                let mut dataset = Dataset::new(df);
+                dataset.meta = read_header(&file_path);
+                dataset.meta.source = Some(file_path.clone());
+                match Provenance::from_file(&file_path) {
+                    Ok(provenance) => dataset.provenance = provenance,
+                    Err(err) => io::print_warn(format!(
+                        "could not hash '{}': {err}", file_path.display()
+                    )),
+                }
+                datasets.insert(name.clone(), dataset);
+                loaded_files.push((name, file_path));
+            }
+            Err(_) => {
+                io::print_error(format!(
+                    "failed to load file '{}'", file_path.display(),
+                ));
+                status = Status::Failed;
+            }
         }
-        // TODO: "load files from directory"
     }
 
     if !loaded_files.is_empty() {
@@ -64,53 +137,70 @@ pub fn run(cmd: LoadCmd, datasets: &mut Datasets) -> Result<bool, clap::Error> {
         }
     } else {
         io::print_error("no valid files were loaded");
+        status = Status::Failed;
     }
 
-    Ok(true)
+    Ok(status)
 }
 
-/*
-fn ask_dataset_name(path: &Path, datasets: &Datasets) -> String {
-    let default_name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap()
-        .to_string();
-
-    loop {
-        let Ok(input) = io::input_prompt("dataset name") else {
-            io::print_error("failed to read input");
-            io::print_error("please, try again");
-            continue;
-        };
+/// Deduces the dataset name for a file from the naming options.
+///
+/// Names derived from the file stem, by default or through `--strip`,
+/// `--regex` and `--template`, are uppercased. A name given with `--as`
+/// is kept as typed.
+fn dataset_name(cmd: &LoadCmd, path: &Path) -> Option<String> {
+    if let Some(name) = &cmd.name {
+        return Some(name.clone());
+    }
+
+    let Some(mut stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        io::print_error(format!(
+            "could not extract a valid name from '{}'",
+            path.display()
+        ));
+        return None;
+    };
+
+    if let Some(prefix) = &cmd.strip {
+        stem = stem.strip_prefix(prefix.as_str()).unwrap_or(stem);
+    }
 
-        let name = input.trim();
-        let final_name = if name.is_empty() {
-            default_name.clone()
-        } else {
-            name.to_string()
+    if let Some(regex) = &cmd.regex {
+        let Some(caps) = regex.captures(stem) else {
+            io::print_error(format!(
+                "'{}' does not match the naming regex", path.display()
+            ));
+            return None;
         };
+        stem = caps.get(1).or_else(|| caps.get(0)).map_or(stem, |m| m.as_str());
+    }
 
-        if datasets.contains_key(&final_name) {
-            io::print_error(format!("name '{final_name}' already exists"));
-            io::print_error("please, choose a different one");
-            continue;
-        }
+    let name = match &cmd.template {
+        Some(template) => template.replace("{}", stem),
+        None => stem.to_string(),
+    };
 
-        return final_name
+    if name.trim().is_empty() {
+        io::print_error(format!(
+            "could not extract a valid name from '{}'",
+            path.display()
+        ));
+        return None;
     }
-}*/
+
+    Some(name.to_ascii_uppercase())
+}
 
 /// Validates the path to a single file and prints errors if it is invalid.
-fn validate_file_path(path: &Path, datasets: &Datasets) -> Option<(String, PathBuf)> {
+fn validate_file_path(path: &Path) -> bool {
     if !path.exists() {
         io::print_error(format!("'file {}' does not exist", path.display()));
-        return None;
+        return false;
     }
 
     if !path.is_file() {
         io::print_error(format!("'{}' is not a file", path.display()));
-        return None;
+        return false;
     }
 
     let ext = path
@@ -119,40 +209,22 @@ fn validate_file_path(path: &Path, datasets: &Datasets) -> Option<(String, PathB
         .map(str::to_ascii_lowercase);
 
     match ext.as_deref() {
-        Some("csv") | Some("xlsx") => {
-            // deduce name
-            let name = match path.file_stem().and_then(|s| s.to_str()) {
-                Some(s) => s.to_ascii_uppercase(),
-                None => {
-                    io::print_error(format!(
-                        "could not extract a valid name from '{}'",
-                        path.display()
-                    ));
-                    return None;
-                }
-            };
-
-            if datasets.contains_key(&name) {
-                io::print_error(format!(
-                    "\"{}\" (from '{}') already exists in memory",
-                    name,
-                    path.display()
-                ));
-                return None;
-            }
-
-            Some((name, path.to_path_buf()))
-        }
+        Some("csv") | Some("xlsx") => true,
         _ => {
             io::print_error(format!(
                 "file '{}' has an unsupported file extension",
                 path.display()
             ));
-            None
+            false
         }
     }
 }
 
+/// Parses a naming regex.
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|err| err.to_string())
+}
+
 /// Validates a directory path and checks for CSV or XLSX files.
 fn validate_dir_path(path: &PathBuf) -> Vec<PathBuf> {
     if !path.exists() {
@@ -208,9 +280,61 @@ fn validate_dir_path(path: &PathBuf) -> Vec<PathBuf> {
     valid_files
 }
 
+/// Extracts metadata from the `#`-prefixed lines at the top of a file.
+///
+/// Invalid values are reported and skipped.
+fn read_header(file_path: &Path) -> Metadata {
+    let mut meta = Metadata::default();
+
+    let Ok(file) = fs::File::open(file_path) else {
+        return meta;
+    };
+
+    let header_lines = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take_while(|line| line.trim_start().starts_with('#'));
+
+    for line in header_lines {
+        if let Err(err) = meta.parse_header_line(&line) {
+            io::print_warn(format!("'{}': {err}", file_path.display()));
+        }
+    }
+
+    meta
+}
+
+/// Renames the columns mapped in the `columns` table of `cone.toml`.
+///
+/// Source names match regardless of case and surrounding spaces.
+fn map_columns(df: &mut DataFrame, file_path: &Path) {
+    for (source, target) in &config::get().columns {
+        let found = df
+            .get_column_names()
+            .into_iter()
+            .find(|name| name.trim().eq_ignore_ascii_case(source.trim()))
+            .cloned();
+        let Some(name) = found else {
+            continue;
+        };
+
+        if name == target.as_str() {
+            continue;
+        }
+        if df.column(target).is_ok() {
+            io::print_warn(format!(
+                "'{}': '{name}' not renamed, '{target}' already exists", file_path.display()
+            ));
+            continue;
+        }
+        let _ = df.rename(&name, target.into());
+    }
+}
+
 fn read_csv(file_path: &Path) -> Result<DataFrame, Box<dyn Error>> {
     let mut lazy_frame = LazyCsvReader::new(file_path)
         .with_infer_schema_length(Some(0))
+        .with_comment_prefix(Some("#".into()))
         .finish()?;
     
     let schema = lazy_frame.collect_schema()?;
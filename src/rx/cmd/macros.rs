@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::{Args, Subcommand};
+
+use crate::rx::cmd::{self, alias, select, Status};
+use crate::rx::config::{self, Kind};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Whether a macro is running, as macros cannot run one another.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Arguments for the `macro` subcommand.
+#[derive(Args, Debug)]
+pub struct MacroCmd {
+    #[command(subcommand)]
+    action: Action,
+}
+
+/// Operations on macros.
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Save a sequence of commands to the project settings.
+    Define {
+        /// Name of the macro.
+        #[arg(value_name = "MACRO")]
+        name: String,
+
+        /// Commands separated by ';' (quote them), where `$1`, `$2`, ...
+        /// stand for the arguments of `macro run` and `$DATASET` for
+        /// each dataset it runs on.
+        #[arg(value_name = "COMMANDS", required = true, num_args = 1.., trailing_var_arg = true, allow_hyphen_values = true)]
+        commands: Vec<String>,
+    },
+    /// Run a macro, once per dataset matching a pattern.
+    Run {
+        /// Name of the macro.
+        #[arg(value_name = "MACRO")]
+        name: String,
+
+        /// Values of the `$1`, `$2`, ... placeholders.
+        #[arg(value_name = "ARGS", allow_negative_numbers = true)]
+        args: Vec<String>,
+
+        /// Glob pattern of the datasets set as `$DATASET`.
+        #[arg(long, value_name = "PATTERN")]
+        on: Option<String>,
+
+        /// Match names regardless of case.
+        #[arg(short = 'i', long, requires = "on")]
+        ignore_case: bool,
+    },
+    /// List the macros defined.
+    List,
+    /// Remove a macro from the project settings.
+    Remove {
+        /// Name of the macro.
+        #[arg(value_name = "MACRO")]
+        name: String,
+    },
+}
+
+/// Executes the `macro` command by saving, running, listing or removing
+/// macros.
+pub fn run(cmd: MacroCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    match cmd.action {
+        Action::Define { name, commands } => {
+            if let Err(err) = alias::check_name(&name) {
+                io::print_error(err);
+                return Ok(Status::Failed);
+            }
+            let commands = alias::join(&commands);
+            match config::define(Kind::Macro, &name, Some(&commands)) {
+                Ok(path) => io::print_info(format!(
+                    "macro '{name}' ({} commands) → '{}' saved",
+                    cmd::split_commands(&commands).len(),
+                    path.display()
+                )),
+                Err(err) => {
+                    io::print_error(err);
+                    return Ok(Status::Failed);
+                }
+            }
+        }
+        Action::Run { name, args, on, ignore_case } => {
+            let Some(commands) = config::get().lookup(Kind::Macro, &name).map(String::from) else {
+                io::print_error(format!("macro '{name}' not found"));
+                return Ok(Status::Failed);
+            };
+
+            if RUNNING.swap(true, Ordering::Relaxed) {
+                io::print_error("macros cannot run other macros");
+                return Ok(Status::Failed);
+            }
+            let result = run_macro(&name, &commands, &args, on.as_deref(), ignore_case, datasets);
+            RUNNING.store(false, Ordering::Relaxed);
+            return result;
+        }
+        Action::List => alias::print_definitions(Kind::Macro),
+        Action::Remove { name } => return Ok(alias::remove(Kind::Macro, &name)),
+    }
+
+    Ok(Status::Done)
+}
+
+/// Runs the commands of a macro once per dataset matching `pattern`, or
+/// once if none is given, stopping at the first command that fails.
+///
+/// Each command is recorded on its own, so it can be undone and traced.
+fn run_macro(
+    name: &str,
+    commands: &str,
+    args: &[String],
+    pattern: Option<&str>,
+    ignore_case: bool,
+    datasets: &mut Datasets,
+) -> Result<Status, clap::Error> {
+    let targets = match pattern {
+        Some(pattern) => {
+            let names = select(datasets, pattern, ignore_case);
+            if names.is_empty() {
+                io::print_error(format!("no datasets match '{pattern}'"));
+                return Ok(Status::Failed);
+            }
+            names.into_iter().map(Some).collect()
+        }
+        None => vec![None],
+    };
+
+    for target in targets {
+        let text = match cmd::substitute(commands, args, target.as_deref()) {
+            Ok((_, used)) if used < args.len() => {
+                io::print_error(format!(
+                    "macro '{name}' takes {used} argument(s), {} given",
+                    args.len()
+                ));
+                return Ok(Status::Failed);
+            }
+            Ok((text, _)) => text,
+            Err(err) => {
+                io::print_error(format!("macro '{name}': {err}"));
+                return Ok(Status::Failed);
+            }
+        };
+
+        match &target {
+            Some(dataset) => io::print_info(format!("{dataset} → macro '{name}' running")),
+            None => io::print_info(format!("macro '{name}' running")),
+        }
+
+        match cmd::execute(&text, datasets)? {
+            Status::Done => {}
+            Status::Failed => {
+                io::print_error(format!("macro '{name}' stopped"));
+                return Ok(Status::Failed);
+            }
+            Status::Exit => return Ok(Status::Exit),
+        }
+    }
+
+    Ok(Status::Done)
+}
@@ -0,0 +1,78 @@
+use clap::Args;
+
+use crate::rx::cmd::Status;
+use crate::rx::data::{META_KEYS, resolve_key};
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `meta` subcommand.
+#[derive(Args, Debug)]
+pub struct MetaCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Metadata entries to store, as KEY=VALUE.
+    #[arg(short, long, value_name = "KEY=VALUE", num_args = 1.., value_parser = parse_entry)]
+    set: Vec<(String, String)>,
+
+    /// Metadata keys to clear.
+    #[arg(short, long, value_name = "KEY", num_args = 1..)]
+    unset: Vec<String>,
+}
+
+/// Executes the `meta` command by editing and printing the metadata
+/// of a dataset.
+pub fn run(cmd: MetaCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get_mut(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    let mut status = Status::Done;
+    for (key, value) in &cmd.set {
+        match dataset.meta.set(key, value) {
+            Ok(()) => io::print_info(format!("'{}' set to '{}'", key, value.trim())),
+            Err(err) => {
+                io::print_error(err);
+                status = Status::Failed;
+            }
+        }
+    }
+
+    for key in &cmd.unset {
+        match dataset.meta.unset(key) {
+            Ok(()) => io::print_info(format!("'{key}' cleared")),
+            Err(err) => {
+                io::print_error(err);
+                status = Status::Failed;
+            }
+        }
+    }
+
+    let records = META_KEYS
+        .iter()
+        .map(|(key, desc)| {
+            let value = dataset.meta.get(key).unwrap_or_else(|| "-".into());
+            vec![key.to_string(), value, desc.to_string()]
+        })
+        .collect();
+
+    let headers = ["key", "value", "description"].map(String::from).to_vec();
+    io::print_records(&cmd.name, "(metadata)", headers, records);
+
+    Ok(status)
+}
+
+/// Parses a `KEY=VALUE` pair, checking that the key is known.
+fn parse_entry(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or("entry must be formatted as KEY=VALUE")?;
+
+    if resolve_key(key).is_none() {
+        return Err(format!("unknown metadata key '{key}'"));
+    }
+
+    Ok((key.trim().to_string(), value.to_string()))
+}
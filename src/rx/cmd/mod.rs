@@ -1,57 +1,610 @@
+pub mod alias;
+pub mod clean;
+pub mod compute;
+pub mod concat;
+pub mod derive;
+pub mod describe;
+pub mod drop;
+pub mod filter;
+pub mod help;
+pub mod history;
+pub mod incline;
+pub mod info;
+pub mod join;
+pub mod list;
 pub mod load;
+pub mod macros;
+pub mod meta;
 pub mod preview;
+pub mod provenance;
+pub mod qa;
+pub mod redo;
+pub mod rename;
+pub mod resample;
 pub mod save;
+pub mod session;
+pub mod smooth;
+pub mod undo;
+pub mod view;
 
-use clap::{Error, Parser, Subcommand};
+use clap::{CommandFactory, Error, Parser, Subcommand};
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
 use shlex::split;
 
 use crate::rx::io;
 use crate::rx::Datasets;
 
+/// Value names of first positional arguments that can be given datasets
+/// from a pipe.
+const PIPED_VALUES: [&str; 3] = ["NAME", "PATTERN", "LEFT"];
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Define, list or remove command aliases.
+    Alias(alias::AliasCmd),
+    /// Clean datasets by removing invalid rows and readings.
+    Clean(clean::CleanCmd),
+    /// Compute derived properties.
+    Compute(compute::ComputeCmd),
+    /// Stack datasets into a new one.
+    Concat(concat::ConcatCmd),
+    /// Derive a column from an expression.
+    Derive(derive::DeriveCmd),
+    /// Print descriptive statistics of datasets.
+    Describe(describe::DescribeCmd),
+    /// Remove datasets from memory.
+    Drop(drop::DropCmd),
+    /// Keep the rows satisfying a condition as a new dataset.
+    Filter(filter::FilterCmd),
+    /// List the commands or describe one of them.
+    Help(help::HelpCmd),
+    /// List the operations that can be undone or redone.
+    History(history::HistoryCmd),
+    /// Correct depth for the inclination of the cone.
+    Incline(incline::InclineCmd),
+    /// Show the columns of datasets.
+    Info(info::InfoCmd),
+    /// Merge two datasets on depth.
+    Join(join::JoinCmd),
+    /// List datasets in memory.
+    List(list::ListCmd),
     /// Load a file or directory.
     Load(load::LoadCmd),
+    /// Define and run sequences of commands.
+    Macro(macros::MacroCmd),
+    /// View or edit dataset metadata.
+    Meta(meta::MetaCmd),
     /// Preview loaded data.
     Preview(preview::PreviewCmd),
+    /// Show or export the commands a dataset was obtained from.
+    Provenance(provenance::ProvenanceCmd),
+    /// Check zero drift and penetration rate (ISO 22476-1).
+    Qa(qa::QaCmd),
+    /// Reapply the last undone operations.
+    Redo(redo::RedoCmd),
+    /// Rename a dataset.
+    Rename(rename::RenameCmd),
+    /// Interpolate a dataset onto new depths.
+    Resample(resample::ResampleCmd),
     /// Save current data.
     Save(save::SaveCmd),
+    /// Save or restore every dataset in memory.
+    Session(session::SessionCmd),
+    /// Smooth noisy channels into new columns.
+    Smooth(smooth::SmoothCmd),
+    /// Revert the last operations on the datasets.
+    Undo(undo::UndoCmd),
+    /// Browse a dataset in a full-screen table.
+    View(view::ViewCmd),
     /// Exit the REPL.
     Exit,
 }
 
+/// How a command finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The command did what it was asked.
+    Done,
+    /// The command reported an error.
+    Failed,
+    /// The command asked to exit.
+    Exit,
+}
+
 /// CONE .:. Read-Eval-Print-Loop <REPL>
 #[derive(Parser, Debug)]
-#[command(multicall = true, disable_help_flag = true)]
+#[command(multicall = true, disable_help_flag = true, disable_help_subcommand = true)]
 struct ReplCli {
     #[command(subcommand)]
     command: Commands,
 }
 
-/// Parses a line of input and executes the corresponding command.
+/// Returns the tree of REPL commands and their arguments.
+pub fn repl_command() -> clap::Command {
+    ReplCli::command()
+}
+
+/// Parses a line of input and executes the corresponding commands.
 ///
-/// Splits the input as shell tokens, parses it into `ReplCli`, and dispatches to handlers.
-pub fn execute(line: &str, datasets: &mut Datasets) -> Result<bool, Error> {
-    // try splitting input into shell-like tokens
-    if let Some(args) = split(line) {
-        match ReplCli::try_parse_from(args) {
-            Ok(cli) => {
-                match cli.command {
-                    Commands::Exit => {
-                        io::print_info("exiting...");
-                        io::print_info("goodbye!");
-                        return Ok(false)
-                    },
-                    Commands::Load(cmd) => return load::run(cmd, datasets),
-                    Commands::Preview(cmd) => return preview::run(cmd, datasets),
-                    Commands::Save(cmd) => save::run(cmd, datasets),
+/// Commands are separated by `;` and run in order, stopping at the first
+/// one that fails or exits. Within each, commands separated by `|` form a
+/// pipeline, such as `load -f a.csv | clean | compute qt`, where every
+/// command after the first receives the datasets output by the previous
+/// one as its first argument.
+///
+/// Aliases are replaced by the commands they stand for beforehand.
+pub fn execute(line: &str, datasets: &mut Datasets) -> Result<Status, Error> {
+    let line = match alias::expand(line) {
+        Ok(line) => line,
+        Err(err) => {
+            io::print_error(err);
+            return Ok(Status::Failed);
+        }
+    };
+
+    for command in split_commands(&line) {
+        let status = run_pipeline(&command, datasets)?;
+        if status != Status::Done {
+            return Ok(status);
+        }
+    }
+    Ok(Status::Done)
+}
+
+/// Executes the commands of a pipeline, stopping at the first one that
+/// fails.
+///
+/// The output of a command is the datasets it added or modified, or those
+/// it was given when it changed none (as with `preview`). A command given
+/// several datasets runs once for each of them.
+fn run_pipeline(line: &str, datasets: &mut Datasets) -> Result<Status, Error> {
+    let stages = split_pipeline(line);
+    let mut piped: Option<Vec<String>> = None;
+
+    for stage in &stages {
+        let Some(args) = split(stage) else {
+            io::print_error("unbalanced quotes in command");
+            return Ok(Status::Failed);
+        };
+        if args.is_empty() {
+            io::print_error("empty command in pipeline");
+            return Ok(Status::Failed);
+        }
+
+        let lines = match &piped {
+            None => vec![stage.clone()],
+            Some(names) => match pipe_into(&args, names) {
+                Ok(lines) => lines,
+                Err(err) => {
+                    io::print_error(err);
+                    return Ok(Status::Failed);
                 }
             },
-            Err(err) => {
-                // print clap-generated error or help message
-                let _ = err.print();
+        };
+
+        let mut outputs = Vec::new();
+        for text in &lines {
+            let (status, changed) = run_command(text, datasets)?;
+            if status != Status::Done {
+                return Ok(status);
+            }
+            outputs.extend(changed);
+        }
+
+        outputs.sort();
+        outputs.dedup();
+        piped = Some(if outputs.is_empty() { piped.unwrap_or_default() } else { outputs });
+    }
+
+    Ok(Status::Done)
+}
+
+/// Builds the commands that pass each of the `names` piped into a
+/// command as its first positional argument.
+fn pipe_into(args: &[String], names: &[String]) -> Result<Vec<String>, String> {
+    let name = &args[0];
+    let cli = ReplCli::command();
+    if let Some(command) = cli.find_subcommand(name)
+        && !takes_piped(command)
+    {
+        return Err(format!("'{name}' does not take datasets from a pipe"));
+    }
+    if names.is_empty() {
+        return Err(format!("no datasets to pipe into '{name}'"));
+    }
+
+    names
+        .iter()
+        .map(|dataset| {
+            let mut args = args.to_vec();
+            args.insert(1, dataset.clone());
+            shlex::try_join(args.iter().map(String::as_str))
+                .map_err(|_| "arguments cannot contain null characters".to_string())
+        })
+        .collect()
+}
+
+/// Returns whether the first positional argument of a command names
+/// datasets, so it can be given them from a pipe.
+pub fn takes_piped(command: &clap::Command) -> bool {
+    command
+        .get_positionals()
+        .next()
+        .and_then(|arg| arg.get_value_names()?.first())
+        .is_some_and(|name| PIPED_VALUES.contains(&name.as_str()))
+}
+
+/// Parses a single command and dispatches it, recording it as typed.
+///
+/// Returns the status of the command with the datasets it changed.
+fn run_command(line: &str, datasets: &mut Datasets) -> Result<(Status, Vec<String>), Error> {
+    // try splitting input into shell-like tokens
+    let Some(args) = split(line) else {
+        io::print_error("unbalanced quotes in command");
+        return Ok((Status::Failed, Vec::new()));
+    };
+
+    match ReplCli::try_parse_from(args) {
+        Ok(cli) => dispatch_recorded(line, cli.command, datasets),
+        Err(err) => {
+            // print clap-generated error or help message
+            let _ = err.print();
+            let status = if err.use_stderr() { Status::Failed } else { Status::Done };
+            Ok((status, Vec::new()))
+        }
+    }
+}
+
+/// Executes a parsed command on the datasets in memory.
+///
+/// Returns [`Status::Exit`] when the command asks to exit.
+pub fn dispatch(command: Commands, datasets: &mut Datasets) -> Result<Status, Error> {
+    match command {
+        Commands::Exit => {
+            io::print_info("exiting...");
+            io::print_info("goodbye!");
+            Ok(Status::Exit)
+        },
+        Commands::Alias(cmd) => alias::run(cmd, datasets),
+        Commands::Clean(cmd) => clean::run(cmd, datasets),
+        Commands::Compute(cmd) => compute::run(cmd, datasets),
+        Commands::Concat(cmd) => concat::run(cmd, datasets),
+        Commands::Derive(cmd) => derive::run(cmd, datasets),
+        Commands::Describe(cmd) => describe::run(cmd, datasets),
+        Commands::Drop(cmd) => drop::run(cmd, datasets),
+        Commands::Filter(cmd) => filter::run(cmd, datasets),
+        Commands::Help(cmd) => help::run(cmd, datasets),
+        Commands::History(cmd) => history::run(cmd, datasets),
+        Commands::Incline(cmd) => incline::run(cmd, datasets),
+        Commands::Info(cmd) => info::run(cmd, datasets),
+        Commands::Join(cmd) => join::run(cmd, datasets),
+        Commands::List(cmd) => list::run(cmd, datasets),
+        Commands::Load(cmd) => load::run(cmd, datasets),
+        Commands::Macro(cmd) => macros::run(cmd, datasets),
+        Commands::Meta(cmd) => meta::run(cmd, datasets),
+        Commands::Preview(cmd) => preview::run(cmd, datasets),
+        Commands::Provenance(cmd) => provenance::run(cmd, datasets),
+        Commands::Qa(cmd) => qa::run(cmd, datasets),
+        Commands::Redo(cmd) => redo::run(cmd, datasets),
+        Commands::Rename(cmd) => rename::run(cmd, datasets),
+        Commands::Resample(cmd) => resample::run(cmd, datasets),
+        Commands::Save(cmd) => save::run(cmd, datasets),
+        Commands::Session(cmd) => session::run(cmd, datasets),
+        Commands::Smooth(cmd) => smooth::run(cmd, datasets),
+        Commands::Undo(cmd) => undo::run(cmd, datasets),
+        Commands::View(cmd) => view::run(cmd, datasets),
+    }
+}
+
+/// Executes a parsed command, recording it as `text` in the undo history
+/// and in the provenance of the datasets it changes.
+///
+/// Commands that browse the history are never recorded, macros record
+/// the commands they run, and restoring a session keeps the provenance
+/// stored with it. Returns the status of the command with the sorted
+/// names of the datasets it added or modified.
+pub fn dispatch_recorded(
+    text: &str,
+    command: Commands,
+    datasets: &mut Datasets,
+) -> Result<(Status, Vec<String>), Error> {
+    let recorded = !matches!(
+        command,
+        Commands::Help(_) | Commands::History(_) | Commands::Macro(_) | Commands::Redo(_) | Commands::Undo(_)
+    );
+    let traced = recorded && !matches!(command, Commands::Session(_));
+
+    // parameters filled in from metadata or settings are recorded too
+    let text = match &command {
+        Commands::Compute(cmd) => compute::explicit(text, cmd, datasets),
+        _ => text.to_string(),
+    };
+
+    // a single snapshot serves to record the command and find its outputs
+    let before = datasets.clone();
+    let result = dispatch(command, datasets);
+    if traced {
+        crate::rx::history::trace(&text, &before, datasets);
+    }
+    let outputs = crate::rx::history::changes(&before, datasets);
+    if recorded {
+        crate::rx::history::record(&text, before, datasets);
+    }
+    result.map(|status| (status, outputs))
+}
+
+/// Splits a line into the commands separated by `;`.
+///
+/// Semicolons within quotes or escaped with a backslash are kept, and empty
+/// commands are skipped.
+pub fn split_commands(line: &str) -> Vec<String> {
+    split_unquoted(line, ';')
+        .into_iter()
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/// Splits a command into the stages of a pipeline, separated by `|`.
+///
+/// Unlike commands, empty stages are kept, as they are errors.
+pub fn split_pipeline(command: &str) -> Vec<String> {
+    split_unquoted(command, '|')
+}
+
+/// Splits a line at the unquoted occurrences of `separator`, trimming
+/// the parts.
+fn split_unquoted(line: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in separators(line) {
+        if c == separator {
+            parts.push(line[start..i].trim().to_string());
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(line[start..].trim().to_string());
+    parts
+}
+
+/// Splits a line into its commands, each with the `;` or `|` that
+/// follows it.
+pub fn split_stages(line: &str) -> Vec<(&str, Option<char>)> {
+    let mut stages = Vec::new();
+    let mut start = 0;
+    for (i, c) in separators(line) {
+        stages.push((&line[start..i], Some(c)));
+        start = i + c.len_utf8();
+    }
+    stages.push((&line[start..], None));
+    stages
+}
+
+/// Replaces the `$1`, `$2`, ... placeholders of the commands in
+/// `template` by `args`, and `$DATASET` by `dataset`, quoting them as
+/// needed.
+///
+/// Returns the commands with the number of arguments referred to.
+pub fn substitute(template: &str, args: &[String], dataset: Option<&str>) -> Result<(String, usize), String> {
+    let placeholder = Regex::new(r"\$(\d+|DATASET)").expect("placeholder regex is valid");
+    let mut used = 0;
+    let mut text = String::new();
+
+    for (stage, separator) in split_stages(template) {
+        let tokens = split(stage).ok_or_else(|| format!("unbalanced quotes in '{}'", stage.trim()))?;
+        let mut replaced = Vec::with_capacity(tokens.len());
+
+        for token in tokens {
+            let mut value = String::new();
+            let mut last = 0;
+            for caps in placeholder.captures_iter(&token) {
+                let whole = caps.get(0).expect("match has a whole group");
+                value.push_str(&token[last..whole.start()]);
+                let arg = match &caps[1] {
+                    "DATASET" => dataset.ok_or("'$DATASET' is only set by 'macro run --on'")?,
+                    n => {
+                        let index: usize = n.parse().map_err(|_| format!("invalid placeholder '${n}'"))?;
+                        if index == 0 {
+                            return Err("placeholders start at '$1'".into());
+                        }
+                        used = used.max(index);
+                        args.get(index - 1)
+                            .ok_or_else(|| format!("missing argument for '${index}'"))?
+                    }
+                };
+                value.push_str(arg);
+                last = whole.end();
+            }
+            value.push_str(&token[last..]);
+            replaced.push(value);
+        }
+
+        let joined = shlex::try_join(replaced.iter().map(String::as_str))
+            .map_err(|_| "arguments cannot contain null characters".to_string())?;
+        text.push_str(&joined);
+        if let Some(c) = separator {
+            text.push_str(&format!(" {c} "));
+        }
+    }
+
+    Ok((text, used))
+}
+
+/// Returns where the command being typed at the end of `input` starts,
+/// and whether it is piped from a previous one.
+pub fn last_stage(input: &str) -> (usize, bool) {
+    separators(input)
+        .last()
+        .map_or((0, false), |&(i, c)| (i + c.len_utf8(), c == '|'))
+}
+
+/// Returns the byte offsets where the commands of a line start, after
+/// each `;` or `|`.
+pub fn stage_starts(line: &str) -> Vec<usize> {
+    let starts = separators(line).into_iter().map(|(i, c)| i + c.len_utf8());
+    std::iter::once(0).chain(starts).collect()
+}
+
+/// Finds the `;` and `|` separators outside quotes, with their byte
+/// offsets.
+///
+/// Escaped characters are skipped, and `||` is kept as the logical
+/// operator of expressions.
+fn separators(line: &str) -> Vec<(usize, char)> {
+    let mut found = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (c, quote) {
+            ('\\', _) => {
+                chars.next();
             }
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('|', None) if chars.peek().is_some_and(|&(_, next)| next == '|') => {
+                chars.next();
+            }
+            (';' | '|', None) => found.push((i, c)),
+            _ => {}
         }
     }
-    Ok(true)
+
+    found
+}
+
+/// Ensures that the provided value is a positive number.
+pub fn parse_positive(s: &str) -> Result<f64, String> {
+    let val: f64 = s.parse().map_err(|_| "argument must be a number")?;
+    if val > 0.0 {
+        Ok(val)
+    } else {
+        Err("argument must be a positive number".into())
+    }
+}
+
+/// Returns the sorted names of datasets matching a glob-like `pattern`.
+///
+/// Supports `*`, `?` and `[...]` wildcards; invalid patterns are
+/// compared literally.
+pub fn select(datasets: &Datasets, pattern: &str, ignore_case: bool) -> Vec<String> {
+    let options = MatchOptions {
+        case_sensitive: !ignore_case,
+        ..MatchOptions::new()
+    };
+
+    let mut names: Vec<String> = match Pattern::new(pattern) {
+        Ok(glob) => datasets
+            .keys()
+            .filter(|name| glob.matches_with(name, options))
+            .cloned()
+            .collect(),
+        Err(_) => datasets
+            .keys()
+            .filter(|name| if ignore_case {
+                name.eq_ignore_ascii_case(pattern)
+            } else {
+                *name == pattern
+            })
+            .cloned()
+            .collect(),
+    };
+
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn commands_split_at_unquoted_semicolons() {
+        assert_eq!(split_commands("load -f a.csv; list ;info A"), ["load -f a.csv", "list", "info A"]);
+        assert_eq!(split_commands("meta A -s 'note=x; y'; list"), ["meta A -s 'note=x; y'", "list"]);
+        assert_eq!(split_commands(r#"alias q "info; list""#), [r#"alias q "info; list""#]);
+        assert_eq!(split_commands(r"meta A -s note=x\;y; list"), [r"meta A -s note=x\;y", "list"]);
+        assert_eq!(split_commands(" ; list;; ;"), ["list"]);
+        assert!(split_commands("").is_empty());
+    }
+
+    #[test]
+    fn pipelines_keep_empty_stages() {
+        assert_eq!(split_pipeline("load -f a.csv | clean | info"), ["load -f a.csv", "clean", "info"]);
+        assert_eq!(split_pipeline("list |"), ["list", ""]);
+        assert_eq!(split_pipeline("| info"), ["", "info"]);
+        assert_eq!(split_pipeline("list || info"), ["list || info"]);
+        assert_eq!(split_pipeline("filter A 'qc > 1 | x' | info"), ["filter A 'qc > 1 | x'", "info"]);
+        assert_eq!(split_pipeline(r"filter A qc\|x | info"), [r"filter A qc\|x", "info"]);
+    }
+
+    #[test]
+    fn unquoted_parts_are_trimmed() {
+        assert_eq!(split_unquoted("  a ;b  ", ';'), ["a", "b"]);
+        assert_eq!(split_unquoted("a|b;c", ';'), ["a|b", "c"]);
+        assert_eq!(split_unquoted("\"a;b\";'c;d'", ';'), ["\"a;b\"", "'c;d'"]);
+        assert_eq!(split_unquoted("", '|'), [""]);
+    }
+
+    #[test]
+    fn stages_keep_their_separators() {
+        assert_eq!(
+            split_stages("load -f a.csv | clean; list"),
+            [("load -f a.csv ", Some('|')), (" clean", Some(';')), (" list", None)]
+        );
+        assert_eq!(stage_starts("a | b;c"), [0, 3, 6]);
+        assert_eq!(last_stage("load -f a.csv | cle"), (15, true));
+        assert_eq!(last_stage("list; inf"), (5, false));
+        assert_eq!(last_stage("filter A 'x | y"), (0, false));
+    }
+
+    #[test]
+    fn commands_report_their_status() {
+        let df = polars::df!("depth (m)" => [0.1, 0.2]).unwrap();
+        let mut datasets = Datasets::from([("A".to_string(), crate::rx::Dataset::new(df))]);
+
+        assert_eq!(execute("drop B; drop A", &mut datasets).unwrap(), Status::Failed);
+        assert!(datasets.contains_key("A"));
+
+        assert_eq!(execute("rename A B | drop", &mut datasets).unwrap(), Status::Done);
+        assert!(datasets.is_empty());
+
+        assert_eq!(execute("exit; list", &mut datasets).unwrap(), Status::Exit);
+    }
+
+    #[test]
+    fn placeholders_are_substituted() {
+        let template = "compute $1 qt -w $2; info $1";
+        let (text, used) = substitute(template, &args(&["CPT-01", "18"]), None).unwrap();
+        assert_eq!((text.as_str(), used), ("compute CPT-01 qt -w 18 ; info CPT-01", 2));
+
+        let (text, used) = substitute("clean $DATASET | info", &[], Some("CPT 02")).unwrap();
+        assert_eq!((text.as_str(), used), ("clean 'CPT 02' | info", 0));
+
+        let (text, _) = substitute("rename $1 ${1}_old", &args(&["A"]), None).unwrap();
+        assert_eq!(text, "rename A '${1}_old'");
+
+        let (text, _) = substitute("derive $1 z = 'qc * $2'", &args(&["A", "2"]), None).unwrap();
+        assert_eq!(split(&text).unwrap(), ["derive", "A", "z", "=", "qc * 2"]);
+
+        let (text, used) = substitute("list", &args(&["extra"]), None).unwrap();
+        assert_eq!((text.as_str(), used), ("list", 0));
+    }
+
+    #[test]
+    fn placeholder_errors() {
+        let error = |template, values: &[&str], dataset| {
+            substitute(template, &args(values), dataset).unwrap_err()
+        };
+
+        assert_eq!(error("info $2", &["A"], None), "missing argument for '$2'");
+        assert_eq!(error("info $0", &["A"], None), "placeholders start at '$1'");
+        assert_eq!(error("info $DATASET", &[], None), "'$DATASET' is only set by 'macro run --on'");
+        assert_eq!(error("info 'A; list", &[], None), "unbalanced quotes in 'info 'A; list'");
+        assert_eq!(error("info A; list \"B", &[], None), "unbalanced quotes in 'list \"B'");
+    }
 }
@@ -1,10 +1,20 @@
 use clap::Args;
+use polars::prelude::*;
 
-use crate::rx::io;
-use crate::rx::Datasets;
+use crate::rx::cmd::{select, Status};
+use crate::rx::io::{self, Precision, TableRows};
+use crate::rx::{Dataset, Datasets};
 
 #[derive(Args, Debug)]
 pub struct PreviewCmd {
+    /// Glob pattern of dataset names to preview.
+    #[arg(value_name = "PATTERN", default_value = "*")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
     /// Number of rows to be shown per dataset.
     #[arg(
         short, long, value_name = "ROWS",
@@ -12,22 +22,105 @@ pub struct PreviewCmd {
         allow_hyphen_values = true
     )]
     rows: isize,
+
+    /// Show both the first and last rows.
+    #[arg(short, long)]
+    both: bool,
+
+    /// Columns to be shown, besides depth.
+    #[arg(short, long, value_name = "COLUMN", num_args = 1..)]
+    columns: Vec<String>,
+
+    /// Shallowest depth to be shown.
+    #[arg(long, value_name = "DEPTH", allow_hyphen_values = true)]
+    from: Option<f64>,
+
+    /// Deepest depth to be shown.
+    #[arg(long, value_name = "DEPTH", allow_hyphen_values = true)]
+    to: Option<f64>,
+
+    /// Show only every Nth row.
+    #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    every: Option<u32>,
+
+    /// Decimal places, for all columns (N) or a single one (COLUMN=N).
+    #[arg(short, long, value_name = "[COLUMN=]N", value_parser = parse_precision)]
+    precision: Vec<(Option<String>, usize)>,
 }
 
 /// Executes the `preview` command by printing a partial view of
-/// each dataset currently in memory.
-pub fn run(cmd: PreviewCmd, datasets: &mut Datasets) -> Result<bool, clap::Error> {
+/// each dataset matching the given pattern.
+pub fn run(cmd: PreviewCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
     if datasets.is_empty() {
         io::print_error("no datasets have been loaded");
-        return Ok(true);
+        return Ok(Status::Failed);
+    }
+
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let precision = Precision::from_pairs(&cmd.precision);
+
+    let n = cmd.rows.unsigned_abs();
+    let rows = if cmd.both {
+        TableRows::HeadTail(n)
+    } else if cmd.rows > 0 {
+        TableRows::Head(n)
+    } else {
+        TableRows::Tail(n)
+    };
+
+    let mut status = Status::Done;
+    for df_name in names {
+        match filtered_view(&cmd, &datasets[&df_name]) {
+            Ok(df) => io::print_table(&df, &df_name, rows, &precision),
+            Err(err) => {
+                io::print_error(format!("{df_name}: {err}"));
+                status = Status::Failed;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Applies the column, depth and sampling filters to a dataset.
+fn filtered_view(cmd: &PreviewCmd, dataset: &Dataset) -> Result<DataFrame, String> {
+    let mut lazy_frame = dataset.data.clone().lazy();
+    let depth = dataset.depth_column();
+
+    if cmd.from.is_some() || cmd.to.is_some() {
+        let depth = depth.as_deref().ok_or("no depth column found")?;
+        if let Some(from) = cmd.from {
+            lazy_frame = lazy_frame.filter(col(depth).gt_eq(lit(from)));
+        }
+        if let Some(to) = cmd.to {
+            lazy_frame = lazy_frame.filter(col(depth).lt_eq(lit(to)));
+        }
+    }
+
+    if !cmd.columns.is_empty() {
+        let mut selected: Vec<String> = depth.into_iter().collect();
+        for column in &cmd.columns {
+            let name = dataset
+                .resolve_column(column)
+                .ok_or(format!("column '{column}' not found"))?;
+            if !selected.contains(&name) {
+                selected.push(name);
+            }
+        }
+        let exprs: Vec<Expr> = selected.iter().map(|name| col(name.as_str())).collect();
+        lazy_frame = lazy_frame.select(exprs);
     }
 
-    for (df_name, df) in datasets.iter() {
-        let n = cmd.rows;
-        io::print_table(df, df_name, n);
+    if let Some(every) = cmd.every {
+        lazy_frame = lazy_frame.select([col("*").gather_every(every as usize, 0)]);
     }
 
-    Ok(true)
+    lazy_frame.collect().map_err(|err| err.to_string())
 }
 
 /// Ensure that the provided value is a non-zero integer.
@@ -40,4 +133,19 @@ fn validate_nonzero(s: &str) -> Result<isize, String> {
     } else {
         Ok(val)
     }
-}
\ No newline at end of file
+}
+
+/// Parses a precision setting formatted as `N` or `COLUMN=N`.
+pub fn parse_precision(s: &str) -> Result<(Option<String>, usize), String> {
+    let (column, places) = match s.rsplit_once('=') {
+        Some((column, places)) => (Some(column.trim().to_string()), places),
+        None => (None, s),
+    };
+
+    let places = places
+        .trim()
+        .parse()
+        .map_err(|_| "decimal places must be a non-negative integer")?;
+
+    Ok((column, places))
+}
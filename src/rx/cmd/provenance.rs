@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, crate_name, crate_version};
+
+use crate::rx::cmd::Status;
+use crate::rx::data::Provenance;
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `provenance` subcommand.
+#[derive(Args, Debug)]
+pub struct ProvenanceCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Write the commands as a script that reproduces the dataset
+    /// (run it with `cone run FILE`).
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Replace the script if it already exists.
+    #[arg(long, requires = "output")]
+    overwrite: bool,
+}
+
+/// Executes the `provenance` command by printing or exporting the files
+/// and commands a dataset was obtained from.
+pub fn run(cmd: ProvenanceCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+    let provenance = &dataset.provenance;
+
+    let Some(output) = &cmd.output else {
+        print_provenance(&cmd.name, provenance);
+        return Ok(Status::Done);
+    };
+
+    if provenance.steps.is_empty() {
+        io::print_error(format!("no commands have been recorded for '{}'", cmd.name));
+        return Ok(Status::Failed);
+    }
+
+    if output.exists() && !cmd.overwrite {
+        io::print_error(format!(
+            "'{}' already exists (use '--overwrite' to replace it)",
+            output.display()
+        ));
+        return Ok(Status::Failed);
+    }
+
+    match fs::write(output, script(&cmd.name, provenance)) {
+        Ok(()) => {
+            io::print_info(format!(
+                "{} → '{}' script saved ({} commands)",
+                cmd.name,
+                output.display(),
+                provenance.steps.len()
+            ));
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(format!("failed to write '{}': {err}", output.display()));
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Prints the source files and the commands applied to a dataset.
+fn print_provenance(name: &str, provenance: &Provenance) {
+    if provenance.sources.is_empty() {
+        io::print_warn(format!("'{name}' was not read from a file"));
+    } else {
+        let records = provenance
+            .sources
+            .iter()
+            .map(|source| vec![source.path.display().to_string(), source.hash.clone()])
+            .collect();
+        let headers = ["file", "blake3"].map(String::from).to_vec();
+        io::print_records(name, "(sources)", headers, records);
+    }
+
+    if provenance.steps.is_empty() {
+        io::print_warn(format!("no commands have been recorded for '{name}'"));
+        return;
+    }
+
+    let records = provenance
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| vec![(i + 1).to_string(), step.command.clone()])
+        .collect();
+    let headers = ["#", "command"].map(String::from).to_vec();
+    io::print_records(name, "(commands)", headers, records);
+}
+
+/// Builds a script replaying the commands applied to a dataset, with
+/// its source files and their hashes as comments.
+fn script(name: &str, provenance: &Provenance) -> String {
+    let mut lines = vec![
+        format!("# provenance of {name}, written by {} {}", crate_name!(), crate_version!()),
+        "# run from the directory the files were loaded from".to_string(),
+    ];
+
+    for source in &provenance.sources {
+        lines.push(format!("# source: {} (blake3 {})", source.path.display(), source.hash));
+    }
+
+    lines.push(String::new());
+    lines.extend(provenance.steps.iter().map(|step| step.command.clone()));
+    lines.push(String::new());
+    lines.join("\n")
+}
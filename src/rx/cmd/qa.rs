@@ -0,0 +1,301 @@
+use clap::Args;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::data::{split_column_name, stress_factor};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Allowable drift as an absolute value in kPa and a percentage of the
+/// measured value, whichever is larger.
+type Limit = (f64, f64);
+
+/// Allowable zero drift of qc, fs and u2 by application class
+/// (ISO 22476-1, Table 2). Class 4 does not require pore pressure.
+const CLASS_LIMITS: [(u8, Limit, Limit, Option<Limit>); 4] = [
+    (1, (35.0, 5.0), (5.0, 10.0), Some((10.0, 2.0))),
+    (2, (100.0, 5.0), (15.0, 15.0), Some((25.0, 3.0))),
+    (3, (200.0, 5.0), (25.0, 15.0), Some((50.0, 5.0))),
+    (4, (500.0, 5.0), (50.0, 20.0), None),
+];
+
+/// Standard penetration rate and its tolerance, in mm/s.
+const PENETRATION_RATE: (f64, f64) = (20.0, 5.0);
+
+/// Labels recognized as the elapsed time channel.
+const TIME_LABELS: [&str; 3] = ["time", "t", "elapsed time"];
+
+/// Arguments for the `qa` subcommand.
+#[derive(Args, Debug)]
+#[command(after_long_help = details())]
+pub struct QaCmd {
+    /// Glob pattern of dataset names to check.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Application class to check against.
+    #[arg(
+        short, long, value_name = "CLASS", default_value_t = 2,
+        value_parser = clap::value_parser!(u8).range(1..=4)
+    )]
+    class: u8,
+}
+
+/// Describes the limits checked, as shown by `help qa`.
+fn details() -> String {
+    let mut lines = vec![
+        "Allowable zero drift (kPa or % of the mean reading, whichever is larger):".to_string(),
+        "  class          qc          fs          u2".to_string(),
+    ];
+    let text = |(absolute, percent): Limit| format!("{absolute} / {percent}%");
+    for (class, qc, fs, u2) in CLASS_LIMITS {
+        let u2 = u2.map_or("-".to_string(), text);
+        lines.push(format!("  {class:<5}{:>12}{:>12}{u2:>12}", text(qc), text(fs)));
+    }
+
+    let (rate, tolerance) = PENETRATION_RATE;
+    lines.extend([
+        String::new(),
+        format!("Penetration rate: {rate} ± {tolerance} mm/s (median of the depth increments)"),
+        "  depth in m, cm or mm; time in s, ms or min, as given by the column units".to_string(),
+        String::new(),
+        "References:".to_string(),
+        "  ISO 22476-1:2022. Geotechnical investigation and testing — Field testing —".to_string(),
+        "    Part 1: Electrical cone and piezocone penetration test, Table 2.".to_string(),
+    ]);
+    lines.join("\n")
+}
+
+/// Outcome of a single check.
+#[derive(Clone, Copy, PartialEq)]
+enum Outcome {
+    Pass,
+    Fail,
+    Missing,
+    NotRequired,
+}
+
+impl Outcome {
+    /// Returns the text shown in the report.
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Pass => "pass",
+            Outcome::Fail => "FAIL",
+            Outcome::Missing => "missing",
+            Outcome::NotRequired => "n/a",
+        }
+    }
+}
+
+/// Executes the `qa` command by checking zero drift and penetration rate
+/// of each matching dataset against an ISO 22476-1 application class.
+pub fn run(cmd: QaCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    for name in names {
+        let checks = check(&datasets[&name], cmd.class);
+
+        let verdict = if checks.iter().any(|(_, _, _, o)| *o == Outcome::Fail) {
+            "FAIL"
+        } else if checks.iter().any(|(_, _, _, o)| *o == Outcome::Missing) {
+            "INCOMPLETE"
+        } else {
+            "PASS"
+        };
+
+        let headers = ["check", "measured", "limit", "result"].map(String::from).to_vec();
+        let records = checks
+            .into_iter()
+            .map(|(check, measured, limit, outcome)| {
+                vec![check, measured, limit, outcome.label().to_string()]
+            })
+            .collect();
+        let subtitle = format!("(application class {}: {verdict})", cmd.class);
+        io::print_records(&name, &subtitle, headers, records);
+    }
+
+    Ok(Status::Done)
+}
+
+/// Runs every check, returning the check name, measured value, limit
+/// and outcome of each.
+fn check(dataset: &Dataset, class: u8) -> Vec<(String, String, String, Outcome)> {
+    let (_, qc_limit, fs_limit, u2_limit) = CLASS_LIMITS[class as usize - 1];
+    let meta = &dataset.meta;
+
+    // qc zero readings are stored in MPa
+    let to_kpa = |(before, after): (Option<f64>, Option<f64>)| {
+        (before.map(|v| v * 1000.0), after.map(|v| v * 1000.0))
+    };
+
+    let mut checks = vec![
+        zero_drift("qc", to_kpa(meta.qc_zero), mean_kpa(dataset, &["qc"]), Some(qc_limit)),
+        zero_drift("fs", meta.fs_zero, mean_kpa(dataset, &["fs"]), Some(fs_limit)),
+        zero_drift("u2", meta.u2_zero, mean_kpa(dataset, &["u2", "u"]), u2_limit),
+    ];
+    checks.push(penetration_rate(dataset));
+    checks
+}
+
+/// Returns the mean absolute reading of a stress channel in kPa, if the
+/// channel exists and its unit is known.
+fn mean_kpa(dataset: &Dataset, labels: &[&str]) -> Option<f64> {
+    let column = dataset.find_column(labels)?;
+    let (_, unit) = split_column_name(&column);
+    let factor = stress_factor(&unit)?;
+    let values = dataset.values(&column)?;
+    let readings: Vec<f64> = values.into_iter().flatten().filter(|v| !v.is_nan()).collect();
+    let mean = readings.iter().map(|v| v.abs()).sum::<f64>() / readings.len() as f64;
+    (!readings.is_empty()).then_some(mean * factor)
+}
+
+/// Compares the change between zero readings of `channel` with the
+/// allowable drift, taking the percentage of its `mean` reading in kPa
+/// when that is larger than the absolute limit.
+fn zero_drift(
+    channel: &str,
+    readings: (Option<f64>, Option<f64>),
+    mean: Option<f64>,
+    limit: Option<Limit>,
+) -> (String, String, String, Outcome) {
+    let name = format!("{channel} zero drift");
+    let Some((absolute, percent)) = limit else {
+        return (name, "-".into(), "-".into(), Outcome::NotRequired);
+    };
+
+    let relative = mean.map_or(0.0, |mean| mean * percent / 100.0);
+    let (limit, limit_text) = if relative > absolute {
+        (relative, format!("{relative:.1} kPa ({percent}% of mean {channel})"))
+    } else {
+        (absolute, format!("{absolute} kPa"))
+    };
+
+    match readings {
+        (Some(before), Some(after)) => {
+            let drift = (after - before).abs();
+            let outcome = if drift <= limit { Outcome::Pass } else { Outcome::Fail };
+            (name, format!("{drift:.1} kPa"), limit_text, outcome)
+        }
+        _ => (name, "-".into(), limit_text, Outcome::Missing),
+    }
+}
+
+/// Checks the median penetration rate, derived from depth and time.
+fn penetration_rate(dataset: &Dataset) -> (String, String, String, Outcome) {
+    let (rate, tolerance) = PENETRATION_RATE;
+    let limit = format!("{rate} ± {tolerance} mm/s");
+
+    let columns = dataset.depth_column().zip(dataset.find_column(&TIME_LABELS));
+    let Some((depth, time)) = columns else {
+        return ("penetration rate".into(), "-".into(), limit, Outcome::Missing);
+    };
+    let name = format!("penetration rate ('{time}')");
+
+    // increments are converted to mm and s from the column units
+    let (Some(mm), Some(seconds)) = (to_mm(&depth), to_seconds(&time)) else {
+        let column = if to_mm(&depth).is_none() { &depth } else { &time };
+        return (name, format!("unknown unit in '{column}'"), limit, Outcome::Missing);
+    };
+    let (Some(depths), Some(times)) = (dataset.values(&depth), dataset.values(&time)) else {
+        return (name, "-".into(), limit, Outcome::Missing);
+    };
+
+    let samples: Vec<(f64, f64)> = depths
+        .into_iter()
+        .zip(&times)
+        .filter_map(|(z, t)| Some((z?, t?)))
+        .collect();
+
+    // rates of each advancing increment, in mm/s
+    let mut rates: Vec<f64> = samples
+        .windows(2)
+        .filter(|w| w[1].0 > w[0].0 && w[1].1 > w[0].1)
+        .map(|w| (w[1].0 - w[0].0) * mm / ((w[1].1 - w[0].1) * seconds))
+        .collect();
+
+    if rates.is_empty() {
+        return (name, "-".into(), limit, Outcome::Missing);
+    }
+
+    // the median is insensitive to pauses for rod changes
+    rates.sort_by(f64::total_cmp);
+    let median = rates[rates.len() / 2];
+    let outside = rates.iter().filter(|r| (*r - rate).abs() > tolerance).count();
+    let share = 100.0 * outside as f64 / rates.len() as f64;
+
+    let outcome = if (median - rate).abs() <= tolerance { Outcome::Pass } else { Outcome::Fail };
+    let measured = format!("{median:.1} mm/s ({share:.0}% of increments outside)");
+    (name, measured, limit, outcome)
+}
+
+/// Returns the factor converting a depth column into millimeters.
+fn to_mm(column: &str) -> Option<f64> {
+    let (_, unit) = split_column_name(column);
+    match unit.trim_start_matches('(').trim_end_matches(')') {
+        "m" => Some(1000.0),
+        "cm" => Some(10.0),
+        "mm" => Some(1.0),
+        _ => None,
+    }
+}
+
+/// Returns the factor converting a time column into seconds.
+fn to_seconds(column: &str) -> Option<f64> {
+    let (_, unit) = split_column_name(column);
+    match unit.trim_start_matches('(').trim_end_matches(')') {
+        "s" | "sec" => Some(1.0),
+        "ms" => Some(0.001),
+        "min" => Some(60.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::df;
+
+    #[test]
+    fn drift_limit_follows_the_mean_reading() {
+        let class_2 = Some((100.0, 5.0));
+        let (_, _, limit, outcome) = zero_drift("qc", (Some(0.0), Some(150.0)), Some(4000.0), class_2);
+        assert_eq!(limit, "200.0 kPa (5% of mean qc)");
+        assert!(matches!(outcome, Outcome::Pass));
+
+        let (_, _, limit, outcome) = zero_drift("qc", (Some(0.0), Some(150.0)), Some(1000.0), class_2);
+        assert_eq!(limit, "100 kPa");
+        assert!(matches!(outcome, Outcome::Fail));
+    }
+
+    #[test]
+    fn penetration_rate_reads_column_units() {
+        let df = df!(
+            "depth (cm)" => [0.0, 2.0, 4.0, 6.0],
+            "time (ms)" => [0.0, 1000.0, 2000.0, 3000.0],
+        )
+        .unwrap();
+        let (name, measured, _, outcome) = penetration_rate(&Dataset::new(df));
+        assert_eq!(name, "penetration rate ('time (ms)')");
+        assert!(measured.starts_with("20.0 mm/s"), "{measured}");
+        assert!(matches!(outcome, Outcome::Pass));
+    }
+
+    #[test]
+    fn penetration_rate_rejects_unknown_units() {
+        let df = df!(
+            "depth (ft)" => [0.0, 1.0],
+            "time (s)" => [0.0, 1.0],
+        )
+        .unwrap();
+        let (_, measured, _, outcome) = penetration_rate(&Dataset::new(df));
+        assert_eq!(measured, "unknown unit in 'depth (ft)'");
+        assert!(matches!(outcome, Outcome::Missing));
+    }
+}
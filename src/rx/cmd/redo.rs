@@ -0,0 +1,29 @@
+use clap::Args;
+
+use crate::rx::cmd::Status;
+use crate::rx::history;
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `redo` subcommand.
+#[derive(Args, Debug)]
+pub struct RedoCmd {
+    /// Number of operations to redo.
+    #[arg(value_name = "STEPS", default_value_t = 1)]
+    steps: usize,
+}
+
+/// Executes the `redo` command by reapplying the last undone operations.
+pub fn run(cmd: RedoCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let labels = history::redo(cmd.steps, datasets);
+    if labels.is_empty() {
+        io::print_warn("nothing to redo");
+        return Ok(Status::Failed);
+    }
+
+    for label in labels {
+        io::print_info(format!("'{label}' redone"));
+    }
+
+    Ok(Status::Done)
+}
@@ -0,0 +1,44 @@
+use clap::Args;
+
+use crate::rx::cmd::Status;
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `rename` subcommand.
+#[derive(Args, Debug)]
+pub struct RenameCmd {
+    /// Current name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// New name for the dataset.
+    #[arg(value_name = "NEW_NAME")]
+    new_name: String,
+
+    /// Replace any dataset already using the new name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `rename` command by moving a dataset to a new name.
+pub fn run(cmd: RenameCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    if !datasets.contains_key(&cmd.name) {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    }
+
+    if datasets.contains_key(&cmd.new_name) && !cmd.overwrite {
+        io::print_error(format!(
+            "dataset '{}' already exists (use '--overwrite' to replace it)",
+            cmd.new_name
+        ));
+        return Ok(Status::Failed);
+    }
+
+    if let Some(dataset) = datasets.remove(&cmd.name) {
+        datasets.insert(cmd.new_name.clone(), dataset);
+        io::print_info(format!("{} → '{}' renamed", cmd.name, cmd.new_name));
+    }
+
+    Ok(Status::Done)
+}
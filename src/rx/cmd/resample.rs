@@ -0,0 +1,392 @@
+use clap::{ArgGroup, Args, ValueEnum};
+use polars::prelude::*;
+
+use crate::rx::cmd::{parse_positive, Status};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Largest number of depths in a regular grid.
+const MAX_GRID_DEPTHS: usize = 1_000_000;
+
+/// Interpolation used to estimate values at the new depths.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// Linear interpolation between the bracketing samples.
+    Linear,
+    /// Value of the closest sample.
+    Nearest,
+    /// Mean of the samples within a moving window centered at each depth.
+    Average,
+    /// Mean of the samples in the block around each depth (downsampling).
+    Block,
+}
+
+/// Arguments for the `resample` subcommand.
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("grid")
+        .args(["step", "like"])
+        .required(true)
+))]
+pub struct ResampleCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Spacing of a regular depth grid, in meters.
+    #[arg(short, long, value_name = "DZ", value_parser = parse_positive)]
+    step: Option<f64>,
+
+    /// Dataset whose depths are used as the grid.
+    #[arg(short, long, value_name = "DATASET")]
+    like: Option<String>,
+
+    /// Estimation method.
+    #[arg(short, long, value_name = "METHOD", default_value = "linear")]
+    method: Method,
+
+    /// Width of the moving-average window, in meters (defaults to the step).
+    #[arg(short, long, value_name = "WIDTH", value_parser = parse_positive)]
+    window: Option<f64>,
+
+    /// First depth of the regular grid.
+    #[arg(long, value_name = "DEPTH", requires = "step")]
+    from: Option<f64>,
+
+    /// Last depth of the regular grid.
+    #[arg(long, value_name = "DEPTH", requires = "step")]
+    to: Option<f64>,
+
+    /// Store the result as a new dataset instead of replacing the original.
+    #[arg(long = "as", value_name = "NAME")]
+    output: Option<String>,
+
+    /// Replace a dataset already using the output name.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Executes the `resample` command by estimating every column of a
+/// dataset at a new set of depths.
+pub fn run(cmd: ResampleCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    let output = cmd.output.clone().unwrap_or_else(|| cmd.name.clone());
+    if output != cmd.name && datasets.contains_key(&output) && !cmd.overwrite {
+        io::print_error(format!(
+            "dataset '{output}' already exists (use '--overwrite' to replace it)"
+        ));
+        return Ok(Status::Failed);
+    }
+
+    let grid = match target_grid(&cmd, dataset, datasets) {
+        Ok(grid) => grid,
+        Err(err) => {
+            io::print_error(format!("{}: {err}", cmd.name));
+            return Ok(Status::Failed);
+        }
+    };
+
+    match resample(dataset, &grid, cmd.method, cmd.window.or(cmd.step)) {
+        Ok(data) => {
+            let (before, after) = (dataset.data.height(), data.height());
+            let resampled = Dataset {
+                data,
+                meta: dataset.meta.clone(),
+                provenance: dataset.provenance.clone(),
+            };
+            datasets.insert(output.clone(), resampled);
+            io::print_info(format!(
+                "{} → '{output}' resampled ({before} → {after} rows)", cmd.name
+            ));
+            Ok(Status::Done)
+        }
+        Err(err) => {
+            io::print_error(format!("{}: {err}", cmd.name));
+            Ok(Status::Failed)
+        }
+    }
+}
+
+/// Builds the depths at which the dataset is resampled.
+fn target_grid(cmd: &ResampleCmd, dataset: &Dataset, datasets: &Datasets) -> Result<Vec<f64>, String> {
+    if let Some(other) = &cmd.like {
+        let other = datasets
+            .get(other)
+            .ok_or(format!("dataset '{other}' not found"))?;
+        let depth = other.depth_column().ok_or("reference dataset has no depth column")?;
+        let values = other.values(&depth).ok_or("reference depth is not numeric")?;
+        let grid: Vec<f64> = values.into_iter().flatten().filter(|d| !d.is_nan()).collect();
+        if !increasing(grid.iter()) {
+            return Err("reference depth must increase monotonically (run 'clean' on it first)".into());
+        }
+        return Ok(grid);
+    }
+
+    let step = cmd.step.ok_or("no grid given")?;
+    let (top, bottom) = dataset.depth_range().ok_or("no depth values found")?;
+    let from = cmd.from.unwrap_or((top / step).ceil() * step);
+    let to = cmd.to.unwrap_or(bottom);
+
+    if to < from {
+        return Err("the grid ends above its start".into());
+    }
+
+    let count = ((to - from) / step + 1e-9).floor() + 1.0;
+    if !count.is_finite() || count > MAX_GRID_DEPTHS as f64 {
+        return Err(format!("the grid would exceed {MAX_GRID_DEPTHS} depths (use a larger step)"));
+    }
+
+    Ok((0..count as usize)
+        .map(|i| ((from + i as f64 * step) * 1e9).round() / 1e9)
+        .collect())
+}
+
+/// Estimates every non-depth column at the given depths.
+fn resample(
+    dataset: &Dataset,
+    grid: &[f64],
+    method: Method,
+    window: Option<f64>,
+) -> Result<DataFrame, String> {
+    let depth = dataset.depth_column().ok_or("no depth column found")?;
+    let depths: Vec<Option<f64>> = dataset
+        .values(&depth)
+        .ok_or("depth column is not numeric")?
+        .into_iter()
+        .collect();
+
+    if !increasing(depths.iter().flatten()) {
+        return Err("depth must increase monotonically (run 'clean' first)".into());
+    }
+
+    if method == Method::Average && window.is_none() {
+        return Err("'average' requires '--window' when resampling onto a dataset".into());
+    }
+
+    let mut columns = Vec::with_capacity(dataset.data.width());
+    for column in dataset.data.get_columns() {
+        if column.name().as_str() == depth {
+            columns.push(Column::new(depth.as_str().into(), grid));
+            continue;
+        }
+
+        let values = column
+            .cast(&DataType::Float64)
+            .map_err(|err| err.to_string())?;
+        let samples: Vec<(f64, f64)> = depths
+            .iter()
+            .zip(values.f64().map_err(|err| err.to_string())?)
+            .filter_map(|(z, v)| Some((z.filter(|z| !z.is_nan())?, v.filter(|v| !v.is_nan())?)))
+            .collect();
+
+        let estimated: Vec<Option<f64>> = match method {
+            Method::Linear => grid.iter().map(|&z| linear(&samples, z)).collect(),
+            Method::Nearest => grid.iter().map(|&z| nearest(&samples, z)).collect(),
+            Method::Average => {
+                let half = window.unwrap_or_default() / 2.0;
+                grid.iter().map(|&z| mean_within(&samples, z - half, z + half)).collect()
+            }
+            Method::Block => block_bounds(grid)
+                .map(|(lo, hi)| mean_within(&samples, lo, hi))
+                .collect(),
+        };
+        columns.push(Column::new(column.name().clone(), estimated));
+    }
+
+    DataFrame::new(columns).map_err(|err| err.to_string())
+}
+
+/// Returns whether every depth is greater than the previous one.
+fn increasing<'a>(depths: impl Iterator<Item = &'a f64> + Clone) -> bool {
+    depths.clone().zip(depths.skip(1)).all(|(a, b)| a < b)
+}
+
+/// Interpolates linearly between the samples bracketing depth `z`.
+fn linear(samples: &[(f64, f64)], z: f64) -> Option<f64> {
+    let i = samples.partition_point(|(zi, _)| *zi < z);
+    let (z1, v1) = *samples.get(i)?;
+    if z1 == z {
+        return Some(v1);
+    }
+    let (z0, v0) = *samples.get(i.checked_sub(1)?)?;
+    Some(v0 + (v1 - v0) * (z - z0) / (z1 - z0))
+}
+
+/// Returns the value of the sample closest to depth `z`, within the
+/// sampled depth range.
+fn nearest(samples: &[(f64, f64)], z: f64) -> Option<f64> {
+    let (first, last) = (samples.first()?.0, samples.last()?.0);
+    if z < first || z > last {
+        return None;
+    }
+    let i = samples.partition_point(|(zi, _)| *zi < z);
+    let candidates = [i.checked_sub(1), Some(i)];
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|j| samples.get(j))
+        .min_by(|a, b| (a.0 - z).abs().total_cmp(&(b.0 - z).abs()))
+        .map(|(_, v)| *v)
+}
+
+/// Averages the samples with depths in `[lo, hi)`.
+fn mean_within(samples: &[(f64, f64)], lo: f64, hi: f64) -> Option<f64> {
+    let start = samples.partition_point(|(zi, _)| *zi < lo);
+    let end = samples.partition_point(|(zi, _)| *zi < hi);
+    let block = samples.get(start..end).unwrap_or_default();
+    (!block.is_empty()).then(|| block.iter().map(|(_, v)| v).sum::<f64>() / block.len() as f64)
+}
+
+/// Returns the block around each grid depth, bounded by the midpoints
+/// to its neighbors.
+///
+/// The end blocks extend as far beyond their depth as toward their
+/// neighbor, and a single depth takes every sample.
+fn block_bounds(grid: &[f64]) -> impl Iterator<Item = (f64, f64)> + '_ {
+    (0..grid.len()).map(move |i| {
+        let lo = match i.checked_sub(1) {
+            Some(prev) => (grid[prev] + grid[i]) / 2.0,
+            None => grid.get(1).map_or(f64::NEG_INFINITY, |next| grid[i] - (next - grid[i]) / 2.0),
+        };
+        let hi = match grid.get(i + 1) {
+            Some(next) => (grid[i] + next) / 2.0,
+            None => i.checked_sub(1).map_or(f64::INFINITY, |prev| grid[i] + (grid[i] - grid[prev]) / 2.0),
+        };
+        (lo, hi)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples with values equal to ten times their depth.
+    fn samples(depths: &[f64]) -> Vec<(f64, f64)> {
+        depths.iter().map(|&z| (z, 10.0 * z)).collect()
+    }
+
+    #[test]
+    fn linear_interpolates_between_samples() {
+        let samples = samples(&[1.0, 2.0, 4.0]);
+        assert_eq!(linear(&samples, 1.5), Some(15.0));
+        assert_eq!(linear(&samples, 3.0), Some(30.0));
+    }
+
+    #[test]
+    fn linear_keeps_samples_at_grid_depths() {
+        let samples = samples(&[1.0, 2.0, 4.0]);
+        assert_eq!(linear(&samples, 1.0), Some(10.0));
+        assert_eq!(linear(&samples, 4.0), Some(40.0));
+    }
+
+    #[test]
+    fn linear_is_undefined_outside_the_samples() {
+        let samples = samples(&[1.0, 2.0]);
+        assert_eq!(linear(&samples, 0.5), None);
+        assert_eq!(linear(&samples, 2.5), None);
+        assert_eq!(linear(&[], 1.0), None);
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_sample() {
+        let samples = samples(&[1.0, 2.0, 4.0]);
+        assert_eq!(nearest(&samples, 1.4), Some(10.0));
+        assert_eq!(nearest(&samples, 1.6), Some(20.0));
+        assert_eq!(nearest(&samples, 3.5), Some(40.0));
+        assert_eq!(nearest(&samples, 4.0), Some(40.0));
+    }
+
+    #[test]
+    fn nearest_is_undefined_outside_the_samples() {
+        let samples = samples(&[1.0, 2.0]);
+        assert_eq!(nearest(&samples, 0.9), None);
+        assert_eq!(nearest(&samples, 2.1), None);
+        assert_eq!(nearest(&[], 1.0), None);
+    }
+
+    #[test]
+    fn block_bounds_split_at_midpoints() {
+        let bounds: Vec<_> = block_bounds(&[1.0, 2.0, 4.0]).collect();
+        assert_eq!(bounds, [(0.5, 1.5), (1.5, 3.0), (3.0, 5.0)]);
+    }
+
+    #[test]
+    fn block_bounds_of_a_single_depth_take_every_sample() {
+        let bounds: Vec<_> = block_bounds(&[5.0]).collect();
+        assert_eq!(bounds, [(f64::NEG_INFINITY, f64::INFINITY)]);
+        assert_eq!(mean_within(&samples(&[4.0, 6.0]), bounds[0].0, bounds[0].1), Some(50.0));
+    }
+
+    #[test]
+    fn edge_blocks_include_their_lower_bound_only() {
+        let samples = samples(&[0.5, 1.0, 3.0, 3.5]);
+        let means: Vec<_> = block_bounds(&[1.0, 2.0, 3.0])
+            .map(|(lo, hi)| mean_within(&samples, lo, hi))
+            .collect();
+        assert_eq!(means, [Some(7.5), None, Some(30.0)]);
+    }
+
+    #[test]
+    fn blocks_outside_the_samples_are_empty() {
+        let samples = samples(&[1.0, 2.0]);
+        assert_eq!(mean_within(&samples, 5.0, 6.0), None);
+        assert_eq!(mean_within(&samples, -2.0, 0.5), None);
+    }
+
+    fn command(step: Option<f64>, like: Option<&str>) -> ResampleCmd {
+        ResampleCmd {
+            name: "A".into(),
+            step,
+            like: like.map(String::from),
+            method: Method::Linear,
+            window: None,
+            from: None,
+            to: None,
+            output: None,
+            overwrite: false,
+        }
+    }
+
+    fn with_reference(reference: &[f64]) -> Datasets {
+        let mut datasets = Datasets::new();
+        let data = df!("Depth (m)" => [0.0, 1.0, 2.0], "qc (MPa)" => [1.0, 2.0, 3.0]).unwrap();
+        datasets.insert("A".into(), Dataset::new(data));
+        let data = df!("Depth (m)" => reference).unwrap();
+        datasets.insert("B".into(), Dataset::new(data));
+        datasets
+    }
+
+    #[test]
+    fn grid_like_requires_increasing_depths() {
+        let datasets = with_reference(&[0.5, 1.5, 1.0]);
+        let err = target_grid(&command(None, Some("B")), &datasets["A"], &datasets).unwrap_err();
+        assert!(err.contains("must increase monotonically"));
+
+        let datasets = with_reference(&[0.5, 1.5]);
+        let grid = target_grid(&command(None, Some("B")), &datasets["A"], &datasets).unwrap();
+        assert_eq!(grid, [0.5, 1.5]);
+    }
+
+    #[test]
+    fn grid_step_is_bounded() {
+        let datasets = with_reference(&[0.5]);
+        let grid = target_grid(&command(Some(0.5), None), &datasets["A"], &datasets).unwrap();
+        assert_eq!(grid, [0.0, 0.5, 1.0, 1.5, 2.0]);
+
+        let err = target_grid(&command(Some(1e-12), None), &datasets["A"], &datasets).unwrap_err();
+        assert!(err.contains("would exceed"));
+
+        let mut unbounded = command(Some(1.0), None);
+        unbounded.to = Some(f64::INFINITY);
+        assert!(target_grid(&unbounded, &datasets["A"], &datasets).is_err());
+    }
+
+    #[test]
+    fn reversed_blocks_are_empty() {
+        assert_eq!(mean_within(&samples(&[1.0, 2.0, 3.0]), 2.5, 1.5), None);
+    }
+}
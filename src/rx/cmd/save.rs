@@ -1,18 +1,159 @@
-use clap::Args;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-//use crate::rx::io;
-use crate::rx::Datasets;
+use clap::{Args, ValueEnum};
+use polars::prelude::*;
 
+use crate::rx::cmd::{select, Status};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Prefix of the metadata keys stored in Parquet files.
+const PARQUET_META_PREFIX: &str = "cone.";
+
+/// File format of saved datasets.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Comma-separated values, with metadata as `#` header lines.
+    Csv,
+    /// Apache Parquet, with metadata as key-value pairs.
+    Parquet,
+}
+
+impl Format {
+    /// Returns the file extension of the format.
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Parquet => "parquet",
+        }
+    }
+
+    /// Returns the format matching the extension of `path`, if any.
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Format::Csv),
+            "parquet" | "pq" => Some(Format::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// Arguments for the `save` subcommand.
+///
+/// A single dataset is written to OUTPUT when it has a file extension.
+/// Otherwise OUTPUT is a directory where each dataset is written as
+/// `NAME.csv` or `NAME.parquet`.
 #[derive(Args, Debug)]
 pub struct SaveCmd {
+    /// Glob pattern of dataset names to save.
     #[arg(value_name = "PATTERN")]
-    pub pattern: String,
+    pattern: String,
 
+    /// Match names regardless of case.
     #[arg(short = 'i', long)]
-    pub ignore_case: bool,
+    ignore_case: bool,
+
+    /// File or directory to write to (the working directory by default).
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// File format (deduced from the output extension, CSV otherwise).
+    #[arg(short, long, value_name = "FORMAT")]
+    format: Option<Format>,
+
+    /// Replace files that already exist.
+    #[arg(long)]
+    overwrite: bool,
 }
 
-pub fn run(_cmd: SaveCmd, _datasets: &mut Datasets) {
-    // implement logic to save the current dataset
-    todo!();
-}
\ No newline at end of file
+/// Executes the `save` command by writing the matching datasets, along
+/// with their metadata, to files.
+pub fn run(cmd: SaveCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    let output = cmd.output.clone().unwrap_or_else(|| PathBuf::from("."));
+    let single_file = Format::from_path(&output).filter(|_| names.len() == 1);
+
+    if single_file.is_none() && output.extension().is_some() && !output.is_dir() {
+        let reason = if names.len() > 1 {
+            format!("'{}' matches {} datasets", cmd.pattern, names.len())
+        } else {
+            "the extension is not supported".to_string()
+        };
+        io::print_error(format!(
+            "cannot write to '{}': {reason} (use a directory)",
+            output.display()
+        ));
+        return Ok(Status::Failed);
+    }
+
+    let mut status = Status::Done;
+    for name in names {
+        let (path, format) = match single_file {
+            Some(format) => (output.clone(), cmd.format.unwrap_or(format)),
+            None => {
+                let format = cmd.format.unwrap_or(Format::Csv);
+                (output.join(format!("{name}.{}", format.extension())), format)
+            }
+        };
+
+        if path.exists() && !cmd.overwrite {
+            io::print_error(format!(
+                "'{}' already exists (use '--overwrite' to replace it)",
+                path.display()
+            ));
+            status = Status::Failed;
+            continue;
+        }
+
+        match save(&datasets[&name], &path, format) {
+            Ok(()) => io::print_info(format!("{name} → '{}' successfully saved", path.display())),
+            Err(err) => {
+                io::print_error(format!("failed to save '{}': {err}", path.display()));
+                status = Status::Failed;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Writes a dataset to `path`, creating missing parent directories.
+pub fn save(dataset: &Dataset, path: &Path, format: Format) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+
+    let mut file = fs::File::create(path).map_err(|err| err.to_string())?;
+    let mut df = dataset.data.clone();
+    let entries = dataset.meta.entries();
+
+    match format {
+        Format::Csv => {
+            for (key, value) in entries {
+                writeln!(file, "# {key}: {value}").map_err(|err| err.to_string())?;
+            }
+            CsvWriter::new(&mut file)
+                .finish(&mut df)
+                .map_err(|err| err.to_string())
+        }
+        Format::Parquet => {
+            let pairs = entries
+                .into_iter()
+                .map(|(key, value)| (format!("{PARQUET_META_PREFIX}{key}"), value))
+                .collect();
+            ParquetWriter::new(&mut file)
+                .with_key_value_metadata(Some(KeyValueMetadata::from_static(pairs)))
+                .finish(&mut df)
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rx::cmd::Status;
+use crate::rx::cmd::save::{self, Format};
+use crate::rx::config::{self, Compute};
+use crate::rx::data::{Metadata, Provenance};
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Directory where the REPL keeps the last session.
+pub const LAST_SESSION: &str = ".cone_session";
+
+/// Name of the file describing the contents of a session.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version of the manifest layout.
+const MANIFEST_VERSION: u32 = 1;
+
+/// Contents of a session directory.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    /// Version of the manifest layout.
+    version: u32,
+    /// Datasets in the session, in name order.
+    datasets: Vec<Entry>,
+    /// Defaults of `compute` in effect when the session was saved.
+    #[serde(default)]
+    compute: Option<Compute>,
+}
+
+/// A dataset stored in a session.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Name of the dataset.
+    name: String,
+    /// Parquet file holding the data, relative to the session directory.
+    file: String,
+    /// Metadata of the dataset.
+    meta: Metadata,
+    /// Files and commands the dataset was obtained from.
+    #[serde(default)]
+    provenance: Provenance,
+}
+
+/// Arguments for the `session` subcommand.
+#[derive(Args, Debug)]
+pub struct SessionCmd {
+    #[command(subcommand)]
+    action: Action,
+}
+
+/// Operations on sessions.
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Save every dataset in memory to a session directory.
+    Save {
+        /// Session directory.
+        #[arg(value_name = "DIR", default_value = LAST_SESSION)]
+        path: PathBuf,
+
+        /// Replace an existing session.
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Load the datasets of a session, replacing those in memory.
+    Load {
+        /// Session directory.
+        #[arg(value_name = "DIR", default_value = LAST_SESSION)]
+        path: PathBuf,
+
+        /// Keep the datasets in memory, replacing those with the same name.
+        #[arg(long)]
+        merge: bool,
+
+        /// Use the defaults of `compute` the session was saved with for
+        /// the rest of the run, instead of those in `cone.toml`.
+        #[arg(long)]
+        settings: bool,
+    },
+}
+
+/// Executes the `session` command by saving or restoring every dataset.
+pub fn run(cmd: SessionCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    match cmd.action {
+        Action::Save { path, overwrite } => {
+            if datasets.is_empty() {
+                io::print_error("no datasets have been loaded");
+                return Ok(Status::Failed);
+            }
+            if path.join(MANIFEST_FILE).exists() && !overwrite {
+                io::print_error(format!(
+                    "session '{}' already exists (use '--overwrite' to replace it)",
+                    path.display()
+                ));
+                return Ok(Status::Failed);
+            }
+            match save_session(&path, datasets) {
+                Ok(count) => io::print_info(format!(
+                    "{count} dataset(s) → session '{}' saved",
+                    path.display()
+                )),
+                Err(err) => {
+                    io::print_error(format!("failed to save session: {err}"));
+                    return Ok(Status::Failed);
+                }
+            }
+        }
+        Action::Load { path, merge, settings } => match load_session(&path) {
+            Ok((loaded, compute)) => {
+                let count = loaded.len();
+                if !merge {
+                    datasets.clear();
+                }
+                datasets.extend(loaded);
+                io::print_info(format!(
+                    "{count} dataset(s) ← session '{}' loaded",
+                    path.display()
+                ));
+                restore_settings(compute, settings);
+            }
+            Err(err) => {
+                io::print_error(format!("failed to load session: {err}"));
+                return Ok(Status::Failed);
+            }
+        },
+    }
+
+    Ok(Status::Done)
+}
+
+/// Writes every dataset to `path` as Parquet files described by a
+/// manifest, returning how many were saved.
+///
+/// The session is written next to `path` and then moved into place, so
+/// a previous session there is only replaced once the new one is
+/// complete.
+pub fn save_session(path: &Path, datasets: &Datasets) -> Result<usize, String> {
+    let is_empty = fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none());
+    if !path.join(MANIFEST_FILE).exists() && !is_empty {
+        return Err(format!("'{}' is not empty and holds no session", path.display()));
+    }
+
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return Err(format!("'{}' is not a valid session directory", path.display()));
+    };
+    let parent = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".{name}.new"));
+    let backup = parent.join(format!(".{name}.old"));
+
+    // leftovers of an interrupted save are discarded
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|err| err.to_string())?;
+    let count = match write_session(&staging, datasets) {
+        Ok(count) => count,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(err);
+        }
+    };
+
+    let replaced = path.exists();
+    if replaced {
+        let _ = fs::remove_dir_all(&backup);
+        fs::rename(path, &backup).map_err(|err| err.to_string())?;
+    }
+    if let Err(err) = fs::rename(&staging, path) {
+        if replaced {
+            let _ = fs::rename(&backup, path);
+        }
+        return Err(err.to_string());
+    }
+    if replaced {
+        let _ = fs::remove_dir_all(&backup);
+    }
+    Ok(count)
+}
+
+/// Writes every dataset and the manifest into the empty directory `dir`.
+fn write_session(dir: &Path, datasets: &Datasets) -> Result<usize, String> {
+    let mut names: Vec<&String> = datasets.keys().collect();
+    names.sort();
+
+    // files are numbered, as names may not be valid file names
+    let mut entries = Vec::with_capacity(names.len());
+    for (i, name) in names.into_iter().enumerate() {
+        let file = format!("dataset-{:03}.parquet", i + 1);
+        let dataset = &datasets[name];
+        save::save(dataset, &dir.join(&file), Format::Parquet)
+            .map_err(|err| format!("{name}: {err}"))?;
+        entries.push(Entry {
+            name: name.clone(),
+            file,
+            meta: dataset.meta.clone(),
+            provenance: dataset.provenance.clone(),
+        });
+    }
+
+    let count = entries.len();
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        datasets: entries,
+        compute: Some(config::get().compute),
+    };
+    let text = serde_json::to_string_pretty(&manifest).map_err(|err| err.to_string())?;
+    fs::write(dir.join(MANIFEST_FILE), text).map_err(|err| err.to_string())?;
+    Ok(count)
+}
+
+/// Reads every dataset of the session stored at `path`, along with the
+/// defaults of `compute` it was saved with, if recorded.
+pub fn load_session(path: &Path) -> Result<(Datasets, Option<Compute>), String> {
+    let manifest = read_manifest(path)?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "session version {} is newer than supported ({MANIFEST_VERSION})",
+            manifest.version
+        ));
+    }
+
+    let mut datasets = Datasets::new();
+    for entry in manifest.datasets {
+        let file = fs::File::open(path.join(&entry.file))
+            .map_err(|err| format!("{}: {err}", entry.file))?;
+        let data = ParquetReader::new(file)
+            .finish()
+            .map_err(|err| format!("{}: {err}", entry.file))?;
+        let dataset = Dataset { data, meta: entry.meta, provenance: entry.provenance };
+        datasets.insert(entry.name, dataset);
+    }
+
+    Ok((datasets, manifest.compute))
+}
+
+/// Compares the defaults of `compute` a session was saved with to those
+/// in effect, using them instead when `adopt` is set and warning about
+/// the differences otherwise.
+///
+/// Sessions saved before the defaults were recorded keep the current ones.
+pub fn restore_settings(saved: Option<Compute>, adopt: bool) {
+    let Some(saved) = saved else {
+        return;
+    };
+    let differences = differences(&saved, &config::get().compute);
+    if differences.is_empty() {
+        return;
+    }
+
+    if adopt {
+        config::set_compute(saved);
+        io::print_info(format!("compute defaults ← session restored ({})", differences.join(", ")));
+    } else {
+        io::print_warn(format!(
+            "the session was saved with other compute defaults ({})",
+            differences.join(", ")
+        ));
+        io::print_warn("those in 'cone.toml' are kept (use 'session load --settings' to restore them)");
+    }
+}
+
+/// Describes the defaults of `compute` that differ between `saved` and
+/// `current`, as changes from the current values.
+fn differences(saved: &Compute, current: &Compute) -> Vec<String> {
+    let text = |value: Option<f64>| value.map_or("unset".to_string(), |v| v.to_string());
+    [
+        ("area_ratio", saved.area_ratio, current.area_ratio),
+        ("gwt", saved.gwt, current.gwt),
+        ("unit_weight", saved.unit_weight, current.unit_weight),
+        ("nkt", saved.nkt, current.nkt),
+    ]
+    .into_iter()
+    .filter(|(_, saved, current)| saved != current)
+    .map(|(key, saved, current)| format!("'compute.{key}' {} → {}", text(current), text(saved)))
+    .collect()
+}
+
+/// Reads the manifest of the session stored at `path`.
+fn read_manifest(path: &Path) -> Result<Manifest, String> {
+    let manifest_path = path.join(MANIFEST_FILE);
+    let text = fs::read_to_string(&manifest_path)
+        .map_err(|_| format!("'{}' holds no session", path.display()))?;
+    serde_json::from_str(&text).map_err(|err| format!("invalid manifest: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differences_list_changed_defaults() {
+        let saved = Compute { gwt: Some(1.0), nkt: Some(14.0), ..Compute::default() };
+        let current = Compute { gwt: Some(2.5), nkt: Some(14.0), unit_weight: Some(18.0), ..saved };
+
+        assert_eq!(
+            differences(&saved, &current),
+            ["'compute.gwt' 2.5 → 1", "'compute.unit_weight' 18 → unset"]
+        );
+        assert!(differences(&saved, &saved).is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+use clap::{Args, ValueEnum};
+use polars::prelude::*;
+
+use crate::rx::cmd::{select, Status};
+use crate::rx::data::split_column_name;
+use crate::rx::io;
+use crate::rx::{Dataset, Datasets};
+
+/// Channels smoothed by default.
+const DEFAULT_CHANNELS: [&str; 2] = ["fs", "u2"];
+
+/// Filter applied to each channel.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// Mean of a centered window of rows.
+    Mean,
+    /// Median of a centered window of rows.
+    Median,
+    /// Savitzky–Golay polynomial fit over a centered window of rows.
+    Savgol,
+    /// Mean of the readings within a depth interval centered at each row.
+    Depth,
+}
+
+impl Filter {
+    /// Returns the suffix used when none is given.
+    fn suffix(self) -> &'static str {
+        match self {
+            Filter::Mean => "_mean",
+            Filter::Median => "_median",
+            Filter::Savgol => "_sg",
+            Filter::Depth => "_avg",
+        }
+    }
+}
+
+/// References shown by `help smooth`.
+const DETAILS: &str = "\
+References:
+  Savitzky, A. & Golay, M.J.E. (1964). Smoothing and differentiation of data
+    by simplified least squares procedures. Analytical Chemistry, 36(8),
+    1627–1639.";
+
+/// Arguments for the `smooth` subcommand.
+///
+/// Results are written to new columns named after the original label
+/// and a suffix, so raw readings are preserved.
+#[derive(Args, Debug)]
+#[command(after_long_help = DETAILS)]
+pub struct SmoothCmd {
+    /// Glob pattern of dataset names to smooth.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+
+    /// Match names regardless of case.
+    #[arg(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Filter to be applied.
+    #[arg(short, long, value_name = "FILTER", default_value = "mean")]
+    method: Filter,
+
+    /// Columns to be smoothed (fs and u2 by default).
+    #[arg(short, long, value_name = "COLUMN", num_args = 1..)]
+    columns: Vec<String>,
+
+    /// Number of rows in the window, an odd number.
+    #[arg(
+        short, long, value_name = "ROWS", default_value_t = 11,
+        value_parser = parse_window
+    )]
+    window: usize,
+
+    /// Depth interval averaged by the 'depth' filter, in meters.
+    #[arg(long, value_name = "METERS", default_value_t = 0.1)]
+    width: f64,
+
+    /// Polynomial order of the 'savgol' filter.
+    #[arg(
+        long, value_name = "ORDER", default_value_t = 2,
+        value_parser = clap::value_parser!(u8).range(0..=5)
+    )]
+    order: u8,
+
+    /// Suffix appended to the label of each smoothed column.
+    #[arg(short, long, value_name = "SUFFIX")]
+    suffix: Option<String>,
+}
+
+/// Executes the `smooth` command by filtering the chosen channels of
+/// each matching dataset.
+pub fn run(cmd: SmoothCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let names = select(datasets, &cmd.pattern, cmd.ignore_case);
+    if names.is_empty() {
+        io::print_error(format!("no datasets match '{}'", cmd.pattern));
+        return Ok(Status::Failed);
+    }
+
+    if cmd.method == Filter::Savgol && cmd.order as usize >= cmd.window {
+        io::print_error("the polynomial order must be lower than the window size");
+        return Ok(Status::Failed);
+    }
+
+    if cmd.method == Filter::Depth && cmd.width <= 0.0 {
+        io::print_error("the averaging width must be a positive number");
+        return Ok(Status::Failed);
+    }
+
+    let mut status = Status::Done;
+    for name in names {
+        let Some(dataset) = datasets.get_mut(&name) else {
+            continue;
+        };
+
+        match smooth(&cmd, dataset) {
+            Ok(columns) => {
+                for column in columns {
+                    io::print_info(format!("{name} → '{column}' computed"));
+                }
+            }
+            Err(err) => {
+                io::print_error(format!("{name}: {err}"));
+                status = Status::Failed;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Filters the chosen channels, returning the names of the new columns.
+fn smooth(cmd: &SmoothCmd, dataset: &mut Dataset) -> Result<Vec<String>, String> {
+    let labels: Vec<&str> = if cmd.columns.is_empty() {
+        DEFAULT_CHANNELS.to_vec()
+    } else {
+        cmd.columns.iter().map(String::as_str).collect()
+    };
+
+    let mut sources = Vec::new();
+    for label in labels {
+        match dataset.resolve_column(label) {
+            Some(name) => sources.push(name),
+            None if cmd.columns.is_empty() => continue,
+            None => return Err(format!("column '{label}' not found")),
+        }
+    }
+
+    if sources.is_empty() {
+        return Err("no columns to smooth (use '--columns')".into());
+    }
+
+    let depths = match cmd.method {
+        Filter::Depth => {
+            let depth = dataset.depth_column().ok_or("no depth column found")?;
+            dataset.read_column(&depth)?
+        }
+        _ => Vec::new(),
+    };
+
+    let suffix = cmd.suffix.as_deref().unwrap_or(cmd.method.suffix());
+    let mut added = Vec::with_capacity(sources.len());
+
+    for source in sources {
+        let values = dataset.read_column(&source)?;
+        let smoothed = match cmd.method {
+            Filter::Mean => rolling(&values, cmd.window, mean),
+            Filter::Median => rolling(&values, cmd.window, median),
+            Filter::Savgol => savitzky_golay(&values, cmd.window, cmd.order as usize),
+            Filter::Depth => depth_average(&depths, &values, cmd.width),
+        };
+
+        let (label, unit) = split_column_name(&source);
+        let target = format!("{label}{suffix} {unit}").trim_end().to_string();
+        if target == source {
+            return Err(format!("the suffix would overwrite '{source}'"));
+        }
+
+        dataset
+            .data
+            .with_column(Column::new(target.as_str().into(), smoothed))
+            .map_err(|err| err.to_string())?;
+        added.push(target);
+    }
+
+    Ok(added)
+}
+
+/// Applies `reduce` to the valid readings of a centered window of rows.
+///
+/// Rows without a reading stay empty, so gaps are not filled in.
+fn rolling(
+    values: &[Option<f64>],
+    window: usize,
+    reduce: fn(&mut [f64]) -> f64,
+) -> Vec<Option<f64>> {
+    let half = window / 2;
+
+    (0..values.len())
+        .map(|i| {
+            values[i].filter(|v| !v.is_nan())?;
+            let lo = i.saturating_sub(half);
+            let hi = (i + half + 1).min(values.len());
+
+            let mut neighbors: Vec<f64> = values[lo..hi]
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|v| !v.is_nan())
+                .collect();
+            Some(reduce(&mut neighbors))
+        })
+        .collect()
+}
+
+/// Arithmetic mean of a non-empty slice.
+fn mean(values: &mut [f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Median of a non-empty slice.
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Fits a polynomial of degree `order` to each centered window of rows
+/// by least squares and evaluates it at the central row.
+///
+/// Near the ends of the sounding the window is shifted inwards instead
+/// of shrinking; missing readings lower the degree when too few remain.
+fn savitzky_golay(values: &[Option<f64>], window: usize, order: usize) -> Vec<Option<f64>> {
+    let half = window / 2;
+
+    (0..values.len())
+        .map(|i| {
+            values[i].filter(|v| !v.is_nan())?;
+            let lo = i.saturating_sub(half).min(values.len().saturating_sub(window));
+            let hi = (lo + window).min(values.len());
+
+            let points: Vec<(f64, f64)> = (lo..hi)
+                .filter_map(|j| {
+                    let v = values[j].filter(|v| !v.is_nan())?;
+                    Some((j as f64 - i as f64, v))
+                })
+                .collect();
+
+            let degree = order.min(points.len() - 1);
+            polynomial_fit(&points, degree).map(|coefficients| coefficients[0])
+        })
+        .collect()
+}
+
+/// Returns the coefficients of the least-squares polynomial of the given
+/// degree, lowest power first, or `None` if the system is singular.
+fn polynomial_fit(points: &[(f64, f64)], degree: usize) -> Option<Vec<f64>> {
+    let n = degree + 1;
+
+    // normal equations as an augmented matrix
+    let mut matrix = vec![vec![0.0; n + 1]; n];
+    for &(x, y) in points {
+        let powers: Vec<f64> = (0..n).map(|k| x.powi(k as i32)).collect();
+        for r in 0..n {
+            for c in 0..n {
+                matrix[r][c] += powers[r] * powers[c];
+            }
+            matrix[r][n] += powers[r] * y;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting
+    for k in 0..n {
+        let pivot = (k..n).max_by(|&a, &b| matrix[a][k].abs().total_cmp(&matrix[b][k].abs()))?;
+        if matrix[pivot][k].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(k, pivot);
+        let (upper, lower) = matrix.split_at_mut(k + 1);
+        let pivot_row = &upper[k];
+        for row in lower {
+            let factor = row[k] / pivot_row[k];
+            for (cell, p) in row.iter_mut().zip(pivot_row).skip(k) {
+                *cell -= factor * p;
+            }
+        }
+    }
+
+    let mut coefficients = vec![0.0; n];
+    for k in (0..n).rev() {
+        let sum: f64 = (k + 1..n).map(|c| matrix[k][c] * coefficients[c]).sum();
+        coefficients[k] = (matrix[k][n] - sum) / matrix[k][k];
+    }
+    Some(coefficients)
+}
+
+/// Averages the readings whose depth lies within `width / 2` of each row.
+fn depth_average(depths: &[Option<f64>], values: &[Option<f64>], width: f64) -> Vec<Option<f64>> {
+    let half = width / 2.0;
+    let samples: Vec<(f64, f64)> = depths
+        .iter()
+        .zip(values)
+        .filter_map(|(z, v)| Some((z.filter(|z| !z.is_nan())?, v.filter(|v| !v.is_nan())?)))
+        .collect();
+
+    depths
+        .iter()
+        .zip(values)
+        .map(|(z, v)| {
+            let z = z.filter(|z| !z.is_nan())?;
+            v.filter(|v| !v.is_nan())?;
+
+            let within: Vec<f64> = samples
+                .iter()
+                .filter(|(zi, _)| (zi - z).abs() <= half)
+                .map(|(_, vi)| *vi)
+                .collect();
+            (!within.is_empty()).then(|| within.iter().sum::<f64>() / within.len() as f64)
+        })
+        .collect()
+}
+
+/// Ensures that the window is an odd number of at least three rows.
+fn parse_window(s: &str) -> Result<usize, String> {
+    let val: usize = s.parse().map_err(|_| "argument must be a whole number")?;
+    if val >= 3 && val % 2 == 1 {
+        Ok(val)
+    } else {
+        Err("the window must be an odd number of at least 3 rows".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that two series match within rounding errors.
+    fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < 1e-9, "{a} != {e}"),
+                _ => assert_eq!(a, e),
+            }
+        }
+    }
+
+    #[test]
+    fn polynomial_fit_recovers_exact_coefficients() {
+        let points: Vec<(f64, f64)> = (-3..=3)
+            .map(|x| x as f64)
+            .map(|x| (x, 2.0 - 0.5 * x + 0.25 * x * x))
+            .collect();
+        let coefficients = polynomial_fit(&points, 2).unwrap();
+        for (c, e) in coefficients.iter().zip([2.0, -0.5, 0.25]) {
+            assert!((c - e).abs() < 1e-9, "{c} != {e}");
+        }
+    }
+
+    #[test]
+    fn polynomial_fit_finds_the_least_squares_line() {
+        let points = [(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 2.0)];
+        let coefficients = polynomial_fit(&points, 1).unwrap();
+        assert!((coefficients[0] - 0.1).abs() < 1e-9);
+        assert!((coefficients[1] - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn polynomial_fit_rejects_singular_systems() {
+        let points = [(1.0, 2.0), (1.0, 3.0)];
+        assert_eq!(polynomial_fit(&points, 1), None);
+    }
+
+    #[test]
+    fn savitzky_golay_matches_tabulated_weights() {
+        // quadratic 5-point weights are (-3, 12, 17, 12, -3) / 35
+        let values = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].map(Some);
+        let smoothed = savitzky_golay(&values, 5, 2);
+        assert_close(&smoothed[2..5], &[Some(12.0 / 35.0), Some(17.0 / 35.0), Some(12.0 / 35.0)]);
+    }
+
+    #[test]
+    fn savitzky_golay_keeps_polynomials_up_to_its_order() {
+        let values: Vec<Option<f64>> = (0..9).map(|x| Some(1.0 + x as f64 * x as f64)).collect();
+        assert_close(&savitzky_golay(&values, 5, 2), &values);
+    }
+
+    #[test]
+    fn savitzky_golay_keeps_missing_readings_and_lowers_the_degree() {
+        let values = [Some(1.0), None, Some(3.0), Some(f64::NAN), Some(5.0)];
+        let smoothed = savitzky_golay(&values, 3, 2);
+        assert_eq!(smoothed[1], None);
+        assert_eq!(smoothed[3], None);
+        assert_close(&[smoothed[0], smoothed[2], smoothed[4]], &[Some(1.0), Some(3.0), Some(5.0)]);
+    }
+
+    #[test]
+    fn savitzky_golay_handles_series_shorter_than_the_window() {
+        let values = [Some(1.0), Some(2.0)];
+        assert_close(&savitzky_golay(&values, 5, 2), &values);
+    }
+}
@@ -0,0 +1,30 @@
+use clap::Args;
+
+use crate::rx::cmd::Status;
+use crate::rx::history;
+use crate::rx::io;
+use crate::rx::Datasets;
+
+/// Arguments for the `undo` subcommand.
+#[derive(Args, Debug)]
+pub struct UndoCmd {
+    /// Number of operations to undo.
+    #[arg(value_name = "STEPS", default_value_t = 1)]
+    steps: usize,
+}
+
+/// Executes the `undo` command by restoring the datasets as they were
+/// before the last operations.
+pub fn run(cmd: UndoCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let labels = history::undo(cmd.steps, datasets);
+    if labels.is_empty() {
+        io::print_warn("nothing to undo");
+        return Ok(Status::Failed);
+    }
+
+    for label in labels {
+        io::print_info(format!("'{label}' undone"));
+    }
+
+    Ok(Status::Done)
+}
@@ -0,0 +1,445 @@
+use std::io::{self as stdio, IsTerminal, Write};
+
+use clap::Args;
+use crossterm::{
+    cursor, event, execute, queue, terminal,
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use polars::prelude::*;
+
+use crate::rx::cmd::Status;
+use crate::rx::cmd::preview::parse_precision;
+use crate::rx::data::split_column_name;
+use crate::rx::io::{self, Precision};
+use crate::rx::{Dataset, Datasets};
+
+/// Number of screen lines used by the title, headers and status bar.
+const CHROME_LINES: u16 = 5;
+
+/// Arguments for the `view` subcommand.
+#[derive(Args, Debug)]
+pub struct ViewCmd {
+    /// Name of the dataset.
+    #[arg(value_name = "NAME")]
+    name: String,
+
+    /// Column whose non-zero values flag rows to be highlighted.
+    #[arg(short, long, value_name = "COLUMN")]
+    flag: Option<String>,
+
+    /// Decimal places, for all columns (N) or a single one (COLUMN=N).
+    #[arg(short, long, value_name = "[COLUMN=]N", value_parser = parse_precision)]
+    precision: Vec<(Option<String>, usize)>,
+}
+
+/// Executes the `view` command by opening a full-screen, scrollable
+/// table of a dataset.
+pub fn run(cmd: ViewCmd, datasets: &mut Datasets) -> Result<Status, clap::Error> {
+    let Some(dataset) = datasets.get(&cmd.name) else {
+        io::print_error(format!("dataset '{}' not found", cmd.name));
+        return Ok(Status::Failed);
+    };
+
+    if dataset.data.width() == 0 {
+        io::print_error(format!("'{}' has no columns to view", cmd.name));
+        return Ok(Status::Failed);
+    }
+
+    if !stdio::stdout().is_terminal() {
+        io::print_error("'view' requires an interactive terminal");
+        return Ok(Status::Failed);
+    }
+
+    let precision = Precision::from_pairs(&cmd.precision);
+
+    let flags = match &cmd.flag {
+        Some(label) => match flagged_rows(dataset, label) {
+            Some(flags) => flags,
+            None => {
+                io::print_error(format!("column '{label}' not found"));
+                return Ok(Status::Failed);
+            }
+        },
+        None => vec![false; dataset.data.height()],
+    };
+
+    let mut viewer = Viewer::new(&cmd.name, dataset, flags, &precision);
+    if let Err(err) = viewer.show() {
+        io::print_error(format!("viewer failed: {err}"));
+        return Ok(Status::Failed);
+    }
+
+    Ok(Status::Done)
+}
+
+/// Returns, for each row, whether the given column holds a non-zero value.
+fn flagged_rows(dataset: &Dataset, label: &str) -> Option<Vec<bool>> {
+    let name = dataset.resolve_column(label)?;
+
+    let column = dataset.data.column(&name).ok()?;
+    let values = column.cast(&DataType::Float64).ok()?;
+    let values = values.f64().ok()?;
+
+    Some(values.into_iter().map(|v| v.is_some_and(|v| v != 0.0)).collect())
+}
+
+/// Pending text input typed at the status bar.
+enum Prompt {
+    /// Depth to jump to.
+    Jump(String),
+    /// Value to search for.
+    Search(String),
+}
+
+/// State of the full-screen table viewer.
+struct Viewer<'a> {
+    /// Name of the dataset shown.
+    name: &'a str,
+    /// Column labels, with the frozen depth column first.
+    labels: Vec<String>,
+    /// Column units, aligned with `labels`.
+    units: Vec<String>,
+    /// Formatted cells, by row.
+    cells: Vec<Vec<String>>,
+    /// Missing values, aligned with `cells`.
+    nulls: Vec<Vec<bool>>,
+    /// Rows to be highlighted.
+    flags: Vec<bool>,
+    /// Display width of each column.
+    widths: Vec<usize>,
+    /// Depth of each row, when a depth column exists.
+    depths: Option<Vec<Option<f64>>>,
+    /// Index of the first visible row.
+    top: usize,
+    /// Index of the selected row.
+    cursor: usize,
+    /// Index of the first visible non-frozen column.
+    left: usize,
+    /// Last searched text.
+    query: Option<String>,
+    /// Pending text input, if any.
+    prompt: Option<Prompt>,
+    /// Message shown in the status bar.
+    status: String,
+}
+
+impl<'a> Viewer<'a> {
+    /// Formats the dataset cells and prepares the initial state.
+    fn new(name: &'a str, dataset: &Dataset, flags: Vec<bool>, precision: &Precision) -> Self {
+        let df = &dataset.data;
+        let depth = dataset.depth_column();
+
+        // move the depth column to the front so it can stay frozen
+        let mut order: Vec<&Column> = df.get_columns().iter().collect();
+        if let Some(pos) = order.iter().position(|c| Some(c.name().as_str()) == depth.as_deref()) {
+            let depth_col = order.remove(pos);
+            order.insert(0, depth_col);
+        }
+
+        let (labels, units): (Vec<String>, Vec<String>) = order
+            .iter()
+            .map(|c| split_column_name(c.name()))
+            .unzip();
+
+        let mut cells = vec![Vec::with_capacity(order.len()); df.height()];
+        let mut nulls = vec![Vec::with_capacity(order.len()); df.height()];
+        for column in &order {
+            let prec = precision.of(column.name());
+            for (i, (row, row_nulls)) in cells.iter_mut().zip(nulls.iter_mut()).enumerate() {
+                let (text, missing) = match column.get(i) {
+                    Ok(AnyValue::Null) | Err(_) => ("null".to_string(), true),
+                    Ok(value) => match value.try_extract::<f64>() {
+                        Ok(v) if v.is_nan() => ("NaN".to_string(), true),
+                        Ok(v) => (format!("{v:.prec$}"), false),
+                        Err(_) => (value.to_string(), false),
+                    },
+                };
+                row.push(text);
+                row_nulls.push(missing);
+            }
+        }
+
+        let widths = (0..order.len())
+            .map(|j| {
+                let cell_width = cells.iter().map(|r| r[j].chars().count()).max().unwrap_or(0);
+                cell_width
+                    .max(labels[j].chars().count())
+                    .max(units[j].chars().count())
+                    + 2
+            })
+            .collect();
+
+        let depths = depth.and_then(|d| dataset.values(&d)).map(|v| v.into_iter().collect());
+
+        Self {
+            name,
+            labels,
+            units,
+            cells,
+            nulls,
+            flags,
+            widths,
+            depths,
+            top: 0,
+            cursor: 0,
+            left: 0,
+            query: None,
+            prompt: None,
+            status: String::new(),
+        }
+    }
+
+    /// Enters the alternate screen and runs the event loop until the
+    /// user quits, restoring the terminal afterwards.
+    fn show(&mut self) -> stdio::Result<()> {
+        let _screen = Screen::enter()?;
+        self.event_loop(&mut stdio::stdout())
+    }
+
+    /// Draws the table and dispatches key presses.
+    fn event_loop(&mut self, stdout: &mut stdio::Stdout) -> stdio::Result<()> {
+        loop {
+            let (width, height) = terminal::size()?;
+            let page = height.saturating_sub(CHROME_LINES).max(1) as usize;
+            self.scroll_to_cursor(page);
+            self.draw(stdout, width as usize, height, page)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.prompt.is_some() {
+                self.handle_prompt(key);
+            } else if !self.handle_key(key, page) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handles a key in navigation mode; returns `false` to quit.
+    fn handle_key(&mut self, key: KeyEvent, page: usize) -> bool {
+        let last = self.cells.len().saturating_sub(1);
+        self.status.clear();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.cursor = (self.cursor + page).min(last),
+            KeyCode::PageUp | KeyCode::Char('b') => self.cursor = self.cursor.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = last,
+            KeyCode::Right | KeyCode::Char('l') if self.left + 2 < self.labels.len() => {
+                self.left += 1;
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.left = self.left.saturating_sub(1),
+            KeyCode::Char('d') => {
+                if self.depths.is_some() {
+                    self.prompt = Some(Prompt::Jump(String::new()));
+                } else {
+                    self.status = "no depth column to jump on".into();
+                }
+            }
+            KeyCode::Char('/') => self.prompt = Some(Prompt::Search(String::new())),
+            KeyCode::Char('n') => self.search_next(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Handles a key while text is being typed at the status bar.
+    fn handle_prompt(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        let (Prompt::Jump(text) | Prompt::Search(text)) = prompt;
+
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Enter => match self.prompt.take() {
+                Some(Prompt::Jump(text)) => self.jump_to(&text),
+                Some(Prompt::Search(text)) => {
+                    self.query = (!text.is_empty()).then_some(text);
+                    self.search_next();
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor to the first row at or below the given depth.
+    fn jump_to(&mut self, text: &str) {
+        let Ok(target) = text.trim().parse::<f64>() else {
+            self.status = format!("invalid depth '{text}'");
+            return;
+        };
+        let Some(depths) = &self.depths else {
+            return;
+        };
+
+        match depths.iter().position(|d| d.is_some_and(|d| d >= target)) {
+            Some(row) => self.cursor = row,
+            None => {
+                self.cursor = depths.len().saturating_sub(1);
+                self.status = format!("depth {target} is beyond the last row");
+            }
+        }
+    }
+
+    /// Moves the cursor to the next row containing the searched text.
+    fn search_next(&mut self) {
+        let Some(query) = &self.query else {
+            self.status = "nothing to search for (press '/')".into();
+            return;
+        };
+
+        let rows = self.cells.len();
+        let found = (1..=rows)
+            .map(|offset| (self.cursor + offset) % rows)
+            .find(|&i| self.cells[i].iter().any(|cell| cell.contains(query.as_str())));
+
+        match found {
+            Some(row) => self.cursor = row,
+            None => self.status = format!("'{query}' not found"),
+        }
+    }
+
+    /// Adjusts the first visible row so the cursor stays on screen.
+    fn scroll_to_cursor(&mut self, page: usize) {
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor >= self.top + page {
+            self.top = self.cursor + 1 - page;
+        }
+    }
+
+    /// Returns the indexes of the columns that fit in the screen width.
+    fn visible_columns(&self, width: usize) -> Vec<usize> {
+        if self.widths.is_empty() {
+            return Vec::new();
+        }
+
+        let mut columns = vec![0];
+        let mut used = self.widths[0];
+        for j in (1 + self.left)..self.labels.len() {
+            if used + self.widths[j] > width {
+                break;
+            }
+            used += self.widths[j];
+            columns.push(j);
+        }
+        columns
+    }
+
+    /// Renders the whole screen.
+    fn draw(&self, out: &mut stdio::Stdout, width: usize, height: u16, page: usize) -> stdio::Result<()> {
+        let columns = self.visible_columns(width);
+        let pad = |text: &str, w: usize| format!("{text:>w$}");
+
+        queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+
+        // title line
+        let end = (self.top + page).min(self.cells.len());
+        let title = format!(
+            " {} {} {}  rows {}–{} of {}  columns {}–{} of {}",
+            io::PROMPT, io::PROMPT, self.name,
+            self.top + 1, end, self.cells.len(),
+            self.left + 2, columns.last().map_or(1, |j| j + 1), self.labels.len(),
+        );
+        queue!(out, SetAttribute(Attribute::Bold), Print(truncate(&title, width)), SetAttribute(Attribute::Reset))?;
+
+        // header lines
+        for (line, texts) in [(1, &self.labels), (2, &self.units)] {
+            queue!(out, cursor::MoveTo(0, line))?;
+            if line == 1 {
+                queue!(out, SetAttribute(Attribute::Bold))?;
+            }
+            for &j in &columns {
+                queue!(out, Print(pad(&texts[j], self.widths[j])))?;
+            }
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        let rule_width: usize = columns.iter().map(|&j| self.widths[j]).sum();
+        queue!(
+            out,
+            cursor::MoveTo(0, 3),
+            SetForegroundColor(Color::DarkGrey),
+            Print("═".repeat(rule_width.min(width))),
+            SetForegroundColor(Color::Reset),
+        )?;
+
+        // data lines
+        for (line, i) in (self.top..end).enumerate() {
+            queue!(out, cursor::MoveTo(0, 4 + line as u16))?;
+            if i == self.cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            } else if self.flags[i] {
+                queue!(out, SetBackgroundColor(Color::DarkYellow))?;
+            }
+            for &j in &columns {
+                let text = pad(&self.cells[i][j], self.widths[j]);
+                if self.nulls[i][j] {
+                    queue!(out, SetForegroundColor(Color::Red), Print(text), SetForegroundColor(Color::Reset))?;
+                } else if j == 0 {
+                    queue!(out, SetAttribute(Attribute::Bold), Print(text), SetAttribute(Attribute::NormalIntensity))?;
+                } else {
+                    queue!(out, Print(text))?;
+                }
+            }
+            queue!(out, SetAttribute(Attribute::Reset), SetBackgroundColor(Color::Reset))?;
+        }
+
+        // status line
+        let status = match &self.prompt {
+            Some(Prompt::Jump(text)) => format!("jump to depth: {text}"),
+            Some(Prompt::Search(text)) => format!("search: {text}"),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => "↑↓ scroll  ←→ columns  d depth  / search  n next  q quit".to_string(),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            SetForegroundColor(Color::DarkGrey),
+            Print(truncate(&status, width)),
+            SetForegroundColor(Color::Reset),
+        )?;
+
+        out.flush()
+    }
+}
+
+/// Shortens `text` to at most `width` characters.
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Full-screen mode of the terminal, left when dropped so that errors
+/// while drawing do not leave the terminal in raw mode.
+struct Screen;
+
+impl Screen {
+    /// Enables raw mode and switches to the alternate screen.
+    fn enter() -> stdio::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let screen = Screen;
+        execute!(stdio::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(stdio::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,464 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use toml_edit::DocumentMut;
+
+/// Name of the project and user configuration files.
+pub const CONFIG_FILE: &str = "cone.toml";
+
+/// Settings in effect, read at startup and updated as aliases and
+/// macros are defined.
+static CONFIG: RwLock<Config> = RwLock::new(Config::new());
+
+/// Settings read from `cone.toml` files.
+///
+/// Every setting is optional; unset ones keep the built-in defaults.
+/// Quantities are always in SI units (m, kPa, MPa, kN/m³), so the unit
+/// system is not a setting.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Settings of the REPL (the `repl` table).
+    pub repl: Repl,
+    /// Settings of tables (the `display` table).
+    pub display: Display,
+    /// Defaults of `compute` (the `compute` table).
+    pub compute: Compute,
+    /// Columns renamed when loading files, as `(source, target)` pairs
+    /// (the `columns` table).
+    #[serde(deserialize_with = "pairs")]
+    pub columns: Vec<(String, String)>,
+    /// Commands typed in place of others, as `(name, command)` pairs
+    /// (the `aliases` table).
+    #[serde(deserialize_with = "pairs")]
+    pub aliases: Vec<(String, String)>,
+    /// Command sequences run by `macro run`, as `(name, commands)` pairs
+    /// (the `macros` table).
+    #[serde(deserialize_with = "pairs")]
+    pub macros: Vec<(String, String)>,
+    /// Files the settings were read from, in order of precedence.
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+}
+
+/// Settings of the REPL.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Repl {
+    /// File where REPL history is stored (`repl.history`), relative to
+    /// the file setting it.
+    pub history: Option<PathBuf>,
+    /// Whether the datasets in memory are saved as the last session when
+    /// the REPL exits (`repl.autosave`), replacing the previous one.
+    pub autosave: Option<bool>,
+}
+
+/// Settings of tables.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Display {
+    /// Maximum width for app rendering (`display.width`).
+    pub width: Option<usize>,
+    /// Decimal places shown in tables (`display.precision`).
+    pub precision: Option<usize>,
+}
+
+/// Parameters `compute` uses when neither the command nor the dataset
+/// metadata gives them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Compute {
+    /// Net area ratio of the cone (`compute.area_ratio`).
+    pub area_ratio: Option<f64>,
+    /// Groundwater table depth in meters (`compute.gwt`).
+    pub gwt: Option<f64>,
+    /// Soil unit weight in kN/m³ (`compute.unit_weight`).
+    pub unit_weight: Option<f64>,
+    /// Cone factor relating net resistance to undrained strength
+    /// (`compute.nkt`).
+    pub nkt: Option<f64>,
+}
+
+/// Kind of user-defined command kept in the settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// A command typed in place of another.
+    Alias,
+    /// A sequence of commands.
+    Macro,
+}
+
+impl Kind {
+    /// Returns the name of the kind, for messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Alias => "alias",
+            Kind::Macro => "macro",
+        }
+    }
+
+    /// Returns the table holding the definitions of this kind.
+    pub fn table(self) -> &'static str {
+        match self {
+            Kind::Alias => "aliases",
+            Kind::Macro => "macros",
+        }
+    }
+}
+
+/// Returns the settings in effect.
+pub fn get() -> RwLockReadGuard<'static, Config> {
+    CONFIG.read().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Locks the settings for an update.
+fn get_mut() -> RwLockWriteGuard<'static, Config> {
+    CONFIG.write().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Reads the user and project configuration files and makes their
+/// settings effective, returning the problems found in them.
+///
+/// Project settings take precedence over user ones. Settings out of
+/// range are skipped, and so are files that are not valid TOML or set
+/// unknown keys.
+pub fn init() -> Vec<String> {
+    let mut config = Config::default();
+    let mut problems = Vec::new();
+
+    let paths = [user_config_path(), project_config_path()];
+    for path in paths.into_iter().flatten() {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => {
+                problems.push(format!("failed to read '{}': {err}", path.display()));
+                continue;
+            }
+        };
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        match parse(&text, base) {
+            Ok((file, invalid)) => {
+                problems.extend(invalid.into_iter().map(|err| format!("{}: {err}", path.display())));
+                config.merge(file);
+                config.files.insert(0, path);
+            }
+            Err((line, err)) => problems.push(format!("{}:{line}: {err} (file skipped)", path.display())),
+        }
+    }
+
+    *get_mut() = config;
+    problems
+}
+
+/// Replaces the defaults of `compute` for the rest of the run, such as
+/// with those a session was saved with when asked to.
+pub fn set_compute(compute: Compute) {
+    get_mut().compute = compute;
+}
+
+/// Stores an alias or macro in the project `cone.toml`, or removes it
+/// when `value` is `None`, and makes the change effective.
+///
+/// The file is created in the working directory if there is none.
+/// Returns the path of the file written.
+pub fn define(kind: Kind, name: &str, value: Option<&str>) -> Result<PathBuf, String> {
+    let path = match project_config_path() {
+        Some(path) => path,
+        None => env::current_dir().map_err(|err| err.to_string())?.join(CONFIG_FILE),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) if !path.exists() => String::new(),
+        Err(err) => return Err(format!("failed to read '{}': {err}", path.display())),
+    };
+
+    let text = edit(&text, kind.table(), name, value).map_err(|err| format!("'{}': {err}", path.display()))?;
+    fs::write(&path, text).map_err(|err| format!("failed to write '{}': {err}", path.display()))?;
+
+    let mut config = get_mut();
+    let definitions = config.definitions_mut(kind);
+    definitions.retain(|(key, _)| key != name);
+    if let Some(value) = value {
+        definitions.push((name.to_string(), value.to_string()));
+    }
+    Ok(path)
+}
+
+/// Returns the nearest `cone.toml` in the working directory or its
+/// ancestors.
+fn project_config_path() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Returns the user-level `cone.toml`, under `$XDG_CONFIG_HOME/cone` or
+/// `~/.config/cone`, if it exists.
+fn user_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+            Some(PathBuf::from(home).join(".config"))
+        })?;
+
+    let path = dir.join("cone").join(CONFIG_FILE);
+    path.is_file().then_some(path)
+}
+
+impl Config {
+    /// Creates empty settings.
+    const fn new() -> Self {
+        Self {
+            repl: Repl { history: None, autosave: None },
+            display: Display { width: None, precision: None },
+            compute: Compute { area_ratio: None, gwt: None, unit_weight: None, nkt: None },
+            columns: Vec::new(),
+            aliases: Vec::new(),
+            macros: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Returns the aliases or macros defined, as `(name, definition)`
+    /// pairs.
+    pub fn definitions(&self, kind: Kind) -> &[(String, String)] {
+        match kind {
+            Kind::Alias => &self.aliases,
+            Kind::Macro => &self.macros,
+        }
+    }
+
+    /// Returns the definition of an alias or macro.
+    pub fn lookup(&self, kind: Kind, name: &str) -> Option<&str> {
+        self.definitions(kind)
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the aliases or macros defined, for editing.
+    fn definitions_mut(&mut self, kind: Kind) -> &mut Vec<(String, String)> {
+        match kind {
+            Kind::Alias => &mut self.aliases,
+            Kind::Macro => &mut self.macros,
+        }
+    }
+
+    /// Overrides these settings with those set in `other`.
+    fn merge(&mut self, other: Config) {
+        self.repl.history = other.repl.history.or(self.repl.history.take());
+        self.repl.autosave = other.repl.autosave.or(self.repl.autosave);
+        self.display.width = other.display.width.or(self.display.width);
+        self.display.precision = other.display.precision.or(self.display.precision);
+
+        let compute = &mut self.compute;
+        compute.area_ratio = other.compute.area_ratio.or(compute.area_ratio);
+        compute.gwt = other.compute.gwt.or(compute.gwt);
+        compute.unit_weight = other.compute.unit_weight.or(compute.unit_weight);
+        compute.nkt = other.compute.nkt.or(compute.nkt);
+
+        for (pairs, others) in [
+            (&mut self.columns, other.columns),
+            (&mut self.aliases, other.aliases),
+            (&mut self.macros, other.macros),
+        ] {
+            for (key, value) in others {
+                pairs.retain(|(existing, _)| *existing != key);
+                pairs.push((key, value));
+            }
+        }
+    }
+
+    /// Discards the settings out of range, returning why.
+    fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.display.width.take_if(|width| !(40..=1000).contains(width)).is_some() {
+            problems.push("'display.width' must be between 40 and 1000".to_string());
+        }
+        if self.display.precision.take_if(|precision| *precision > 12).is_some() {
+            problems.push("'display.precision' must be between 0 and 12".to_string());
+        }
+        if self.compute.area_ratio.take_if(|ratio| !(0.0..=1.0).contains(ratio)).is_some() {
+            problems.push("'compute.area_ratio' must be between 0 and 1".to_string());
+        }
+        if self.compute.unit_weight.take_if(|weight| *weight <= 0.0).is_some() {
+            problems.push("'compute.unit_weight' must be positive".to_string());
+        }
+        if self.compute.nkt.take_if(|nkt| *nkt <= 0.0).is_some() {
+            problems.push("'compute.nkt' must be positive".to_string());
+        }
+
+        problems
+    }
+}
+
+/// Reads the settings written in `text`, resolving relative paths
+/// against `base`.
+///
+/// Returns the settings with the problems found in them, or the line
+/// and message of the error that makes the whole text invalid.
+fn parse(text: &str, base: &Path) -> Result<(Config, Vec<String>), (usize, String)> {
+    let mut config: Config = toml::from_str(text).map_err(|err| {
+        let line = err.span().map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+        (line, err.message().trim().to_string())
+    })?;
+
+    config.repl.history = config.repl.history.map(|path| base.join(path));
+    let problems = config.validate();
+    Ok((config, problems))
+}
+
+/// Reads a table of strings as `(key, value)` pairs, in file order.
+fn pairs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(String, String)>, D::Error> {
+    toml::Table::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| match value {
+            toml::Value::String(value) => Ok((key, value)),
+            other => Err(D::Error::custom(format!(
+                "'{key}' must be a string, got {}",
+                other.type_str()
+            ))),
+        })
+        .collect()
+}
+
+/// Sets `key` to the string `value` in `table` of a configuration file,
+/// or removes the entry when `value` is `None`, keeping the rest of the
+/// file as written.
+fn edit(text: &str, table: &str, key: &str, value: Option<&str>) -> Result<String, String> {
+    let mut document: DocumentMut = text.parse().map_err(|err: toml_edit::TomlError| {
+        format!("invalid TOML ({})", err.message().trim())
+    })?;
+
+    match value {
+        Some(value) => {
+            let entries = document
+                .entry(table)
+                .or_insert(toml_edit::table())
+                .as_table_like_mut()
+                .ok_or(format!("'{table}' is not a table"))?;
+            entries.insert(key, toml_edit::value(value));
+        }
+        None => {
+            if let Some(entries) = document.get_mut(table).and_then(|item| item.as_table_like_mut()) {
+                entries.remove(key);
+            }
+        }
+    }
+
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_table() {
+        let text = r#"
+[repl]
+history = "logs/history.txt"
+autosave = true
+
+[display]
+width = 120
+precision = 3
+
+[compute]
+area_ratio = 0.8
+gwt = -1
+nkt = 14
+
+[columns]
+"Cone resistance" = "qc (MPa)"
+
+[aliases]
+qt = "compute $1 qt"
+ls = 'list'
+"#;
+        let (config, problems) = parse(text, Path::new("/project")).unwrap();
+
+        assert!(problems.is_empty());
+        assert_eq!(config.repl.history, Some(PathBuf::from("/project/logs/history.txt")));
+        assert_eq!(config.repl.autosave, Some(true));
+        assert_eq!((config.display.width, config.display.precision), (Some(120), Some(3)));
+        assert_eq!(config.compute.area_ratio, Some(0.8));
+        assert_eq!(config.compute.gwt, Some(-1.0));
+        assert_eq!(config.compute.nkt, Some(14.0));
+        assert_eq!(config.compute.unit_weight, None);
+        assert_eq!(config.columns, [("Cone resistance".into(), "qc (MPa)".into())]);
+        assert_eq!(
+            config.aliases,
+            [("qt".into(), "compute $1 qt".into()), ("ls".into(), "list".into())]
+        );
+    }
+
+    #[test]
+    fn skips_settings_out_of_range() {
+        let text = "[display]\nwidth = 10\nprecision = 2\n[compute]\narea_ratio = 1.5\nnkt = 0\n";
+        let (config, problems) = parse(text, Path::new(".")).unwrap();
+
+        assert_eq!(config.display.width, None);
+        assert_eq!(config.display.precision, Some(2));
+        assert_eq!(config.compute.area_ratio, None);
+        assert_eq!(config.compute.nkt, None);
+        assert_eq!(problems.len(), 3);
+    }
+
+    #[test]
+    fn locates_invalid_files() {
+        let error = |text| parse(text, Path::new(".")).unwrap_err();
+
+        assert_eq!(error("[display]\nwidth = 80\nwidht = 80\n").0, 3);
+        assert_eq!(error("[display]\n\nwidth = \"wide\"\n").0, 3);
+        assert_eq!(error("[columns]\nqc = \"qc (MPa)\"\nqc = \"qt\"\n").0, 3);
+        assert_eq!(error("[compute]\ngwt = \n").0, 2);
+        assert!(error("[units]\nsystem = \"si\"\n").1.contains("units"));
+        assert!(error("[aliases]\nls = 1\n").1.contains("'ls' must be a string"));
+    }
+
+    #[test]
+    fn merges_over_earlier_files() {
+        let user = "[display]\nwidth = 80\nprecision = 2\n[aliases]\na = \"list\"\nb = \"info\"\n";
+        let project = "[display]\nwidth = 100\n[aliases]\na = \"describe\"\n";
+        let (mut config, _) = parse(user, Path::new(".")).unwrap();
+        let (project, _) = parse(project, Path::new(".")).unwrap();
+        config.merge(project);
+
+        assert_eq!((config.display.width, config.display.precision), (Some(100), Some(2)));
+        assert_eq!(config.lookup(Kind::Alias, "a"), Some("describe"));
+        assert_eq!(config.lookup(Kind::Alias, "b"), Some("info"));
+    }
+
+    #[test]
+    fn edits_keep_the_rest_of_the_file() {
+        let text = "# project settings\n[display]\nwidth = 80 # narrow terminal\n\n\
+                    [aliases]\nls = \"list\"\n";
+
+        let added = edit(text, "aliases", "qt", Some("compute $1 qt; info \"$1\"")).unwrap();
+        assert_eq!(added, format!("{text}qt = 'compute $1 qt; info \"$1\"'\n"));
+        let (config, _) = parse(&added, Path::new(".")).unwrap();
+        assert_eq!(config.lookup(Kind::Alias, "qt"), Some("compute $1 qt; info \"$1\""));
+
+        let replaced = edit(&added, "aliases", "ls", Some("list -l")).unwrap();
+        assert!(replaced.contains("ls = \"list -l\"\nqt = "));
+
+        let removed = edit(&added, "aliases", "qt", None).unwrap();
+        assert_eq!(removed, text);
+
+        let created = edit(text, "macros", "prep", Some("clean $DATASET")).unwrap();
+        assert!(created.ends_with("\n[macros]\nprep = \"clean $DATASET\"\n"));
+        assert_eq!(edit("", "aliases", "ls", None).unwrap(), "");
+        assert!(edit("aliases = 1\n", "aliases", "ls", Some("list")).is_err());
+        assert!(edit("[aliases\n", "aliases", "ls", Some("list")).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use std::path::{Path, PathBuf};
+
+use polars::prelude::{ChunkAgg, DataFrame, DataType, Float64Chunked};
+use serde::{Deserialize, Serialize};
+
+/// Unit weight of water (kN/m³).
+pub const WATER_UNIT_WEIGHT: f64 = 9.81;
+
+/// Metadata keys accepted by `meta` and file headers, with descriptions.
+pub const META_KEYS: [(&str, &str); 14] = [
+    ("area_ratio", "net area ratio of the cone (-)"),
+    ("gwt", "groundwater table depth (m)"),
+    ("easting", "x coordinate of the sounding"),
+    ("northing", "y coordinate of the sounding"),
+    ("elevation", "ground surface elevation (m)"),
+    ("qc_zero_before", "qc zero reading before the test (MPa)"),
+    ("qc_zero_after", "qc zero reading after the test (MPa)"),
+    ("fs_zero_before", "fs zero reading before the test (kPa)"),
+    ("fs_zero_after", "fs zero reading after the test (kPa)"),
+    ("u2_zero_before", "u2 zero reading before the test (kPa)"),
+    ("u2_zero_after", "u2 zero reading after the test (kPa)"),
+    ("date", "date of the test"),
+    ("cone_id", "identifier of the cone used"),
+    ("source", "file the dataset was loaded from"),
+];
+
+/// A CPTu sounding held in memory along with its metadata.
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    /// Measured and computed channels.
+    pub data: DataFrame,
+    /// Descriptive information about the sounding.
+    pub meta: Metadata,
+    /// Record of how the dataset was obtained.
+    pub provenance: Provenance,
+}
+
+impl Dataset {
+    /// Creates a dataset with empty metadata.
+    pub fn new(data: DataFrame) -> Self {
+        Self { data, meta: Metadata::default(), provenance: Provenance::default() }
+    }
+
+    /// Returns the full name of the first column matching any of `labels`.
+    ///
+    /// Matching ignores case and the unit enclosed in parentheses.
+    pub fn find_column(&self, labels: &[&str]) -> Option<String> {
+        self.data
+            .get_column_names()
+            .into_iter()
+            .find(|name| {
+                let (label, _) = split_column_name(name);
+                labels.iter().any(|l| l.eq_ignore_ascii_case(&label))
+            })
+            .map(|name| name.to_string())
+    }
+
+    /// Returns the full name of a column given either its label or
+    /// its full name.
+    pub fn resolve_column(&self, label: &str) -> Option<String> {
+        self.find_column(&[label]).or_else(|| {
+            let column = self.data.column(label).ok()?;
+            Some(column.name().to_string())
+        })
+    }
+
+    /// Returns the full name of a column given either its label or its
+    /// full name, failing when there is none.
+    pub fn require_column(&self, label: &str) -> Result<String, String> {
+        self.resolve_column(label)
+            .ok_or_else(|| format!("column '{label}' not found"))
+    }
+
+    /// Reads a column given by label or full name as floats, keeping
+    /// nulls in place.
+    pub fn read_column(&self, label: &str) -> Result<Vec<Option<f64>>, String> {
+        let name = self.require_column(label)?;
+        let column = self
+            .data
+            .column(&name)
+            .and_then(|c| c.cast(&DataType::Float64))
+            .map_err(|err| err.to_string())?;
+        Ok(column
+            .f64()
+            .map_err(|err| err.to_string())?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns the name of the depth column, if any.
+    pub fn depth_column(&self) -> Option<String> {
+        self.find_column(&["depth", "z"])
+    }
+
+    /// Returns the minimum and maximum values of the depth column.
+    pub fn depth_range(&self) -> Option<(f64, f64)> {
+        let depth = self.depth_column()?;
+        let values = self.values(&depth)?;
+        Some((values.min()?, values.max()?))
+    }
+
+    /// Returns the values of a numeric column.
+    pub fn values(&self, name: &str) -> Option<Float64Chunked> {
+        let column = self.data.column(name).ok()?;
+        column.as_materialized_series().f64().ok().cloned()
+    }
+}
+
+/// Record of the files and commands a dataset was obtained from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Provenance {
+    /// Files read, with their BLAKE3 hashes.
+    pub sources: Vec<Source>,
+    /// Commands applied since loading, in the order they ran.
+    pub steps: Vec<Step>,
+}
+
+/// A file a dataset was read from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    /// Path of the file, as given when loading.
+    pub path: PathBuf,
+    /// BLAKE3 hash of the file contents, in hexadecimal.
+    pub hash: String,
+}
+
+/// A command that changed a dataset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// Position of the command among those run, used to order the
+    /// steps of merged records.
+    pub seq: u64,
+    /// Command line, as entered.
+    pub command: String,
+}
+
+impl Provenance {
+    /// Creates the record of a dataset read from `path`.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+        let hash = blake3::hash(&bytes).to_hex().to_string();
+        let source = Source { path: path.to_path_buf(), hash };
+        Ok(Self { sources: vec![source], steps: Vec::new() })
+    }
+
+    /// Combines the records of datasets merged into a new one, keeping
+    /// shared sources and steps once.
+    pub fn merge<'a>(records: impl IntoIterator<Item = &'a Provenance>) -> Self {
+        let mut merged = Self::default();
+        for record in records {
+            for source in &record.sources {
+                if !merged.sources.contains(source) {
+                    merged.sources.push(source.clone());
+                }
+            }
+            for step in &record.steps {
+                if !merged.steps.contains(step) {
+                    merged.steps.push(step.clone());
+                }
+            }
+        }
+        merged.steps.sort_by_key(|step| step.seq);
+        merged
+    }
+
+    /// Returns the position following the last step recorded.
+    pub fn next_seq(&self) -> u64 {
+        self.steps.last().map_or(0, |step| step.seq + 1)
+    }
+}
+
+/// Descriptive information attached to a dataset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Net area ratio of the cone.
+    pub area_ratio: Option<f64>,
+    /// Groundwater table depth (m).
+    pub gwt: Option<f64>,
+    /// X coordinate of the sounding.
+    pub easting: Option<f64>,
+    /// Y coordinate of the sounding.
+    pub northing: Option<f64>,
+    /// Ground surface elevation (m).
+    pub elevation: Option<f64>,
+    /// Zero readings of qc before and after the test (MPa).
+    pub qc_zero: (Option<f64>, Option<f64>),
+    /// Zero readings of fs before and after the test (kPa).
+    pub fs_zero: (Option<f64>, Option<f64>),
+    /// Zero readings of u2 before and after the test (kPa).
+    pub u2_zero: (Option<f64>, Option<f64>),
+    /// Date of the test, as written in the source.
+    pub date: Option<String>,
+    /// Identifier of the cone used.
+    pub cone_id: Option<String>,
+    /// File the dataset was loaded from.
+    pub source: Option<PathBuf>,
+}
+
+impl Metadata {
+    /// Returns the value stored under `key` formatted as text.
+    pub fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "date" => self.date.clone(),
+            "cone_id" => self.cone_id.clone(),
+            "source" => self.source.as_ref().map(|p| p.display().to_string()),
+            _ => self.number(key).map(|v| v.to_string()),
+        }
+    }
+
+    /// Returns the keys that are set and their values, in the order
+    /// of [`META_KEYS`].
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        META_KEYS
+            .iter()
+            .filter_map(|(key, _)| Some((*key, self.get(key)?)))
+            .collect()
+    }
+
+    /// Returns the numeric value stored under a canonical `key`.
+    pub fn number(&self, key: &str) -> Option<f64> {
+        match key {
+            "area_ratio" => self.area_ratio,
+            "gwt" => self.gwt,
+            "easting" => self.easting,
+            "northing" => self.northing,
+            "elevation" => self.elevation,
+            "qc_zero_before" => self.qc_zero.0,
+            "qc_zero_after" => self.qc_zero.1,
+            "fs_zero_before" => self.fs_zero.0,
+            "fs_zero_after" => self.fs_zero.1,
+            "u2_zero_before" => self.u2_zero.0,
+            "u2_zero_after" => self.u2_zero.1,
+            _ => None,
+        }
+    }
+
+    /// Returns the slot of a numeric canonical `key`.
+    fn number_mut(&mut self, key: &str) -> Option<&mut Option<f64>> {
+        match key {
+            "area_ratio" => Some(&mut self.area_ratio),
+            "gwt" => Some(&mut self.gwt),
+            "easting" => Some(&mut self.easting),
+            "northing" => Some(&mut self.northing),
+            "elevation" => Some(&mut self.elevation),
+            "qc_zero_before" => Some(&mut self.qc_zero.0),
+            "qc_zero_after" => Some(&mut self.qc_zero.1),
+            "fs_zero_before" => Some(&mut self.fs_zero.0),
+            "fs_zero_after" => Some(&mut self.fs_zero.1),
+            "u2_zero_before" => Some(&mut self.u2_zero.0),
+            "u2_zero_after" => Some(&mut self.u2_zero.1),
+            _ => None,
+        }
+    }
+
+    /// Stores `value` under `key`, parsing numbers where required.
+    ///
+    /// `key` may be any alias accepted by [`resolve_key`].
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let Some(key) = resolve_key(key) else {
+            return Err(format!("unknown metadata key '{key}'"));
+        };
+
+        let value = value.trim();
+        match key {
+            "date" => self.date = Some(value.to_string()),
+            "cone_id" => self.cone_id = Some(value.to_string()),
+            "source" => self.source = Some(PathBuf::from(value)),
+            _ => {
+                let number = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{key}' must be a number, got '{value}'"))?;
+                if key == "area_ratio" && !(0.0..=1.0).contains(&number) {
+                    return Err("'area_ratio' must be between 0 and 1".into());
+                }
+                if let Some(slot) = self.number_mut(key) {
+                    *slot = Some(number);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the value stored under `key`.
+    pub fn unset(&mut self, key: &str) -> Result<(), String> {
+        let Some(key) = resolve_key(key) else {
+            return Err(format!("unknown metadata key '{key}'"));
+        };
+
+        match key {
+            "date" => self.date = None,
+            "cone_id" => self.cone_id = None,
+            "source" => self.source = None,
+            _ => {
+                if let Some(slot) = self.number_mut(key) {
+                    *slot = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a `# key: value` (or `# key = value`) header line.
+    ///
+    /// Unknown keys are ignored so vendor-specific headers do not
+    /// interfere with loading.
+    pub fn parse_header_line(&mut self, line: &str) -> Result<bool, String> {
+        let Some(body) = line.trim().strip_prefix('#') else {
+            return Ok(false);
+        };
+
+        let Some((key, value)) = body.split_once([':', '=']) else {
+            return Ok(false);
+        };
+
+        if resolve_key(key).is_none() {
+            return Ok(false);
+        }
+
+        self.set(key, value).map(|_| true)
+    }
+}
+
+/// Maps a metadata key or one of its common aliases to its canonical name.
+pub fn resolve_key(key: &str) -> Option<&'static str> {
+    let norm = key
+        .trim()
+        .to_ascii_lowercase()
+        .replace([' ', '-', '.'], "_");
+
+    let canonical = match norm.as_str() {
+        "area_ratio" | "net_area_ratio" | "a" | "an" => "area_ratio",
+        "gwt" | "groundwater_depth" | "water_depth" | "gwl" => "gwt",
+        "easting" | "x" => "easting",
+        "northing" | "y" => "northing",
+        "elevation" | "ground_elevation" | "z0" => "elevation",
+        "qc_zero_before" | "zero_qc_before" | "qc_zero_initial" => "qc_zero_before",
+        "qc_zero_after" | "zero_qc_after" | "qc_zero_final" => "qc_zero_after",
+        "fs_zero_before" | "zero_fs_before" | "fs_zero_initial" => "fs_zero_before",
+        "fs_zero_after" | "zero_fs_after" | "fs_zero_final" => "fs_zero_after",
+        "u2_zero_before" | "zero_u2_before" | "u2_zero_initial" => "u2_zero_before",
+        "u2_zero_after" | "zero_u2_after" | "u2_zero_final" => "u2_zero_after",
+        "date" | "test_date" => "date",
+        "cone_id" | "cone" => "cone_id",
+        "source" => "source",
+        _ => return None,
+    };
+    Some(canonical)
+}
+
+/// Splits a column name such as `qc (MPa)` into its label and unit.
+///
+/// The unit keeps its enclosing parentheses and is empty when absent.
+pub fn split_column_name(name: &str) -> (String, String) {
+    if let Some((label, unit)) = name.rsplit_once('(') {
+        (
+            label.trim().to_string(),
+            format!("({})", unit.trim().trim_end_matches(')')),
+        )
+    } else {
+        // fallback if there's no unit
+        (name.to_string(), String::new())
+    }
+}
+
+/// Returns the factor converting a stress `unit` into kPa.
+pub fn stress_factor(unit: &str) -> Option<f64> {
+    let unit = unit.trim_start_matches('(').trim_end_matches(')');
+    match unit.to_ascii_lowercase().as_str() {
+        "kpa" | "kn/m2" | "kn/m²" => Some(1.0),
+        "mpa" | "mn/m2" | "mn/m²" => Some(1000.0),
+        "pa" => Some(0.001),
+        "bar" => Some(100.0),
+        _ => None,
+    }
+}
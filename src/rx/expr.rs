@@ -0,0 +1,651 @@
+use std::f64::consts::PI;
+
+use polars::prelude::*;
+
+use crate::rx::Dataset;
+use crate::rx::data::{WATER_UNIT_WEIGHT, resolve_key};
+
+/// Functions accepted in expressions, with their number of arguments
+/// and a short description.
+const FUNCTIONS: [(&str, usize, &str); 20] = [
+    ("abs", 1, "absolute value"),
+    ("sqrt", 1, "square root"),
+    ("exp", 1, "exponential"),
+    ("ln", 1, "natural logarithm"),
+    ("log10", 1, "base 10 logarithm"),
+    ("log", 2, "logarithm of x in a given base"),
+    ("pow", 2, "x raised to a power"),
+    ("sin", 1, "sine of radians"),
+    ("cos", 1, "cosine of radians"),
+    ("tan", 1, "tangent of radians"),
+    ("asin", 1, "arcsine, in radians"),
+    ("acos", 1, "arccosine, in radians"),
+    ("atan", 1, "arctangent, in radians"),
+    ("rad", 1, "degrees to radians"),
+    ("deg", 1, "radians to degrees"),
+    ("floor", 1, "largest integer not above x"),
+    ("ceil", 1, "smallest integer not below x"),
+    ("round", 2, "x rounded to a number of decimals"),
+    ("if", 3, "second argument where the first holds, else the third"),
+    ("isnull", 1, "whether x is missing"),
+];
+
+/// Functions taking any number of arguments (at least one).
+const VARIADIC: [(&str, &str); 2] = [
+    ("min", "smallest of the arguments"),
+    ("max", "largest of the arguments"),
+];
+
+/// Named constants accepted in expressions.
+const CONSTANTS: [(&str, f64); 2] = [("pi", PI), ("gamma_w", WATER_UNIT_WEIGHT)];
+
+/// Prefix of references to dataset metadata (e.g. `meta.gwt`).
+const META_PREFIX: &str = "meta.";
+
+/// Unary operators.
+#[derive(Debug, Clone, Copy)]
+pub enum Unary {
+    Neg,
+    Not,
+}
+
+/// Binary operators.
+#[derive(Debug, Clone, Copy)]
+pub enum Binary {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Eq,
+    NotEq,
+    And,
+    Or,
+}
+
+/// Syntax tree of a parsed expression.
+#[derive(Debug, Clone)]
+pub enum Node {
+    /// Numeric literal or named constant.
+    Number(f64),
+    /// Column given by label or full name.
+    Column(String),
+    /// Numeric metadata value given by key.
+    Meta(String),
+    /// Operation on a single operand.
+    Unary(Unary, Box<Node>),
+    /// Operation on two operands.
+    Binary(Binary, Box<Node>, Box<Node>),
+    /// Function applied to its arguments.
+    Call(String, Vec<Node>),
+}
+
+/// Lexical unit of an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Quoted(String),
+    Symbol(&'static str),
+}
+
+/// Describes the expression syntax, as shown by `help derive`.
+pub fn reference() -> String {
+    const ARGS: [&str; 3] = ["x", "y", "z"];
+
+    let mut lines = vec![
+        "Syntax:".to_string(),
+        "  columns     qc, fs, 'qc (MPa)' (quoted when the name has spaces)".to_string(),
+        format!("  metadata    {META_PREFIX}gwt, {META_PREFIX}area_ratio, ..."),
+        "  arithmetic  + - * / ^".to_string(),
+        "  comparison  < <= > >= == (or =) !=".to_string(),
+        "  logic       and (&&), or (||), not (!)".to_string(),
+        String::new(),
+        "Constants:".to_string(),
+    ];
+    lines.extend(CONSTANTS.iter().map(|(name, value)| format!("  {name:<12}{value}")));
+
+    lines.push(String::new());
+    lines.push("Functions:".to_string());
+    for (name, arity, desc) in FUNCTIONS {
+        let call = format!("{name}({})", ARGS[..arity].join(", "));
+        lines.push(format!("  {call:<16}{desc}"));
+    }
+    for (name, desc) in VARIADIC {
+        let call = format!("{name}(x, ...)");
+        lines.push(format!("  {call:<16}{desc}"));
+    }
+
+    lines.join("\n")
+}
+
+/// Parses an expression such as `(qt - sv0) / 14`.
+///
+/// The syntax tree is compiled separately for each dataset, so column
+/// labels and metadata are resolved against that dataset.
+///
+/// Columns are referred to by label or, when they contain spaces, by
+/// their name in single quotes (e.g. `'qc (MPa)'`).
+pub fn parse(text: &str) -> Result<Node, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let node = parser.or()?;
+
+    match parser.tokens.get(parser.pos) {
+        None => Ok(node),
+        Some((token, at)) => Err(format!("unexpected {} at position {at}", describe(token))),
+    }
+}
+
+impl Node {
+    /// Compiles the tree into a polars expression for `dataset`,
+    /// resolving column labels and metadata values.
+    pub fn compile(&self, dataset: &Dataset) -> Result<Expr, String> {
+        Ok(match self {
+            Node::Number(value) => lit(*value),
+            Node::Column(label) => {
+                let name = dataset
+                    .resolve_column(label)
+                    .ok_or(format!("column '{label}' not found"))?;
+                let dtype = dataset
+                    .data
+                    .column(&name)
+                    .map_err(|err| err.to_string())?
+                    .dtype();
+                match dtype {
+                    DataType::Boolean => col(&name),
+                    _ => col(&name).cast(DataType::Float64),
+                }
+            }
+            Node::Meta(key) => {
+                let canonical = resolve_key(key).ok_or(format!("unknown metadata key '{key}'"))?;
+                let value = dataset
+                    .meta
+                    .number(canonical)
+                    .ok_or(format!("metadata '{canonical}' is not set or not numeric"))?;
+                lit(value)
+            }
+            Node::Unary(op, operand) => {
+                let operand = operand.compile(dataset)?;
+                match op {
+                    Unary::Neg => lit(0.0) - operand,
+                    Unary::Not => operand.not(),
+                }
+            }
+            Node::Binary(op, left, right) => {
+                let (l, r) = (left.compile(dataset)?, right.compile(dataset)?);
+                match op {
+                    Binary::Add => l + r,
+                    Binary::Sub => l - r,
+                    Binary::Mul => l * r,
+                    Binary::Div => l / r,
+                    Binary::Pow => l.pow(r),
+                    Binary::Lt => l.lt(r),
+                    Binary::LtEq => l.lt_eq(r),
+                    Binary::Gt => l.gt(r),
+                    Binary::GtEq => l.gt_eq(r),
+                    Binary::Eq => l.eq(r),
+                    Binary::NotEq => l.neq(r),
+                    Binary::And => l.and(r),
+                    Binary::Or => l.or(r),
+                }
+            }
+            Node::Call(name, args) => compile_call(name, args, dataset)?,
+        })
+    }
+}
+
+/// Compiles a function call, checking its number of arguments.
+fn compile_call(name: &str, args: &[Node], dataset: &Dataset) -> Result<Expr, String> {
+    if let Some((_, arity, _)) = FUNCTIONS.iter().find(|(n, _, _)| *n == name) {
+        if args.len() != *arity {
+            let plural = if *arity == 1 { "argument" } else { "arguments" };
+            return Err(format!("'{name}' takes {arity} {plural}, {} given", args.len()));
+        }
+    } else if VARIADIC.iter().any(|(n, _)| *n == name) {
+        if args.is_empty() {
+            return Err(format!("'{name}' takes at least 1 argument"));
+        }
+    } else {
+        return Err(format!("unknown function '{name}'"));
+    }
+
+    // the number of decimals must be known before evaluation
+    if name == "round" {
+        let Node::Number(decimals) = args[1] else {
+            return Err("'round' takes a constant number of decimals".into());
+        };
+        if decimals < 0.0 || decimals.fract() != 0.0 {
+            return Err("'round' takes a whole number of decimals".into());
+        }
+        let x = args[0].compile(dataset)?;
+        return Ok(x.round(decimals as u32, RoundMode::HalfAwayFromZero));
+    }
+
+    let mut exprs: Vec<Expr> = args
+        .iter()
+        .map(|arg| arg.compile(dataset))
+        .collect::<Result<_, _>>()?;
+
+    Ok(match name {
+        "min" | "max" => {
+            // missing values are skipped, as long as one argument is known
+            let mut args = exprs.into_iter();
+            let first = args.next().ok_or(format!("'{name}' takes at least 1 argument"))?;
+            args.fold(first, |acc, x| {
+                let better = if name == "min" { x.clone().lt(acc.clone()) } else { x.clone().gt(acc.clone()) };
+                when(acc.clone().is_null().or(better)).then(x).otherwise(acc)
+            })
+        }
+        "if" => {
+            let mut args = exprs.into_iter();
+            let (condition, then, otherwise) = (args.next(), args.next(), args.next());
+            match (condition, then, otherwise) {
+                (Some(c), Some(t), Some(o)) => when(c).then(t).otherwise(o),
+                _ => return Err("'if' takes 3 arguments".into()),
+            }
+        }
+        "log" => {
+            let Node::Number(base) = args[1] else {
+                return Err("'log' takes a constant base".into());
+            };
+            exprs.swap_remove(0).log(base)
+        }
+        "pow" => {
+            let exponent = exprs.swap_remove(1);
+            exprs.swap_remove(0).pow(exponent)
+        }
+        _ => {
+            let x = exprs.swap_remove(0);
+            match name {
+                "abs" => x.abs(),
+                "sqrt" => x.sqrt(),
+                "exp" => x.exp(),
+                "ln" => x.log(std::f64::consts::E),
+                "log10" => x.log(10.0),
+                "sin" => x.sin(),
+                "cos" => x.cos(),
+                "tan" => x.tan(),
+                "asin" => x.arcsin(),
+                "acos" => x.arccos(),
+                "atan" => x.arctan(),
+                "rad" => x * lit(PI / 180.0),
+                "deg" => x * lit(180.0 / PI),
+                "floor" => x.floor(),
+                "ceil" => x.ceil(),
+                "isnull" => x.is_null(),
+                _ => return Err(format!("unknown function '{name}'")),
+            }
+        }
+    })
+}
+
+/// Splits an expression into tokens paired with their positions.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    const SYMBOLS: [&str; 18] = [
+        "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "^", "<", ">", "!", "(", ")", ",",
+        "=",
+    ];
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let at = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // exponent, as in 1.5e-3
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(char::is_ascii_digit) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = literal
+                .parse()
+                .map_err(|_| format!("invalid number '{literal}' at position {at}"))?;
+            tokens.push((Token::Number(value), at));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.')) {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), at));
+        } else if c == '\'' {
+            let start = i + 1;
+            let end = chars[start..]
+                .iter()
+                .position(|&c| c == '\'')
+                .ok_or(format!("unterminated quote at position {at}"))?;
+            tokens.push((Token::Quoted(chars[start..start + end].iter().collect()), at));
+            i = start + end + 1;
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or(format!("unexpected '{c}' at position {at}"))?;
+            i += symbol.chars().count();
+            // a single '=' is read as equality
+            let symbol = if *symbol == "=" { "==" } else { symbol };
+            tokens.push((Token::Symbol(symbol), at));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Returns a token as shown in error messages.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(value) => format!("number '{value}'"),
+        Token::Ident(name) => format!("'{name}'"),
+        Token::Quoted(name) => format!("column '{name}'"),
+        Token::Symbol(symbol) => format!("'{symbol}'"),
+    }
+}
+
+/// Recursive descent parser, one method per precedence level from
+/// lowest to highest.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    /// Returns the next token without consuming it.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// Consumes the next token if it is one of the given symbols or
+    /// keywords, returning its canonical symbol.
+    fn accept(&mut self, options: &[(&str, &'static str)]) -> Option<&'static str> {
+        let found = options.iter().find(|(text, _)| match self.peek() {
+            Some(Token::Symbol(s)) => s == text,
+            Some(Token::Ident(word)) => word.eq_ignore_ascii_case(text),
+            _ => false,
+        })?;
+        self.pos += 1;
+        Some(found.1)
+    }
+
+    /// Consumes the given symbol or fails.
+    fn expect(&mut self, symbol: &'static str) -> Result<(), String> {
+        match self.tokens.get(self.pos) {
+            Some((Token::Symbol(s), _)) if *s == symbol => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some((token, at)) => Err(format!(
+                "expected '{symbol}' but found {} at position {at}",
+                describe(token)
+            )),
+            None => Err(format!("expected '{symbol}' at the end of the expression")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut node = self.and()?;
+        while self.accept(&[("||", "or"), ("or", "or")]).is_some() {
+            node = Node::Binary(Binary::Or, Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut node = self.not()?;
+        while self.accept(&[("&&", "and"), ("and", "and")]).is_some() {
+            node = Node::Binary(Binary::And, Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.accept(&[("!", "not"), ("not", "not")]).is_some() {
+            return Ok(Node::Unary(Unary::Not, Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, String> {
+        let node = self.additive()?;
+        let op = match self.accept(&[
+            ("<=", "<="),
+            (">=", ">="),
+            ("==", "=="),
+            ("!=", "!="),
+            ("<", "<"),
+            (">", ">"),
+        ]) {
+            Some("<=") => Binary::LtEq,
+            Some(">=") => Binary::GtEq,
+            Some("==") => Binary::Eq,
+            Some("!=") => Binary::NotEq,
+            Some("<") => Binary::Lt,
+            Some(">") => Binary::Gt,
+            _ => return Ok(node),
+        };
+        Ok(Node::Binary(op, Box::new(node), Box::new(self.additive()?)))
+    }
+
+    fn additive(&mut self) -> Result<Node, String> {
+        let mut node = self.multiplicative()?;
+        while let Some(symbol) = self.accept(&[("+", "+"), ("-", "-")]) {
+            let op = if symbol == "+" { Binary::Add } else { Binary::Sub };
+            node = Node::Binary(op, Box::new(node), Box::new(self.multiplicative()?));
+        }
+        Ok(node)
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+        while let Some(symbol) = self.accept(&[("*", "*"), ("/", "/")]) {
+            let op = if symbol == "*" { Binary::Mul } else { Binary::Div };
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.accept(&[("-", "-")]).is_some() {
+            return Ok(Node::Unary(Unary::Neg, Box::new(self.unary()?)));
+        }
+        if self.accept(&[("+", "+")]).is_some() {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    /// Powers bind tighter than a leading minus (`-2^2` is -4) and
+    /// associate to the right.
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.primary()?;
+        if self.accept(&[("^", "^")]).is_some() {
+            return Ok(Node::Binary(Binary::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let Some((token, at)) = self.tokens.get(self.pos).cloned() else {
+            return Err("unexpected end of expression".into());
+        };
+        self.pos += 1;
+
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Quoted(name) => Ok(Node::Column(name)),
+            Token::Symbol("(") => {
+                let node = self.or()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Ident(name) if self.peek() == Some(&Token::Symbol("(")) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::Symbol(")")) {
+                    args.push(self.or()?);
+                    while self.accept(&[(",", ",")]).is_some() {
+                        args.push(self.or()?);
+                    }
+                }
+                self.expect(")")?;
+                Ok(Node::Call(name.to_ascii_lowercase(), args))
+            }
+            Token::Ident(name) => {
+                if let Some(key) = name.strip_prefix(META_PREFIX) {
+                    return Ok(Node::Meta(key.to_string()));
+                }
+                if let Some((_, value)) = CONSTANTS.iter().find(|(c, _)| c.eq_ignore_ascii_case(&name)) {
+                    return Ok(Node::Number(*value));
+                }
+                if ["and", "or", "not"].iter().any(|k| k.eq_ignore_ascii_case(&name)) {
+                    return Err(format!("unexpected '{name}' at position {at}"));
+                }
+                Ok(Node::Column(name))
+            }
+            token => Err(format!("unexpected {} at position {at}", describe(&token))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a tree in prefix form, e.g. `(+ 1 (* 2 3))`.
+    fn render(node: &Node) -> String {
+        match node {
+            Node::Number(value) => value.to_string(),
+            Node::Column(name) => name.clone(),
+            Node::Meta(key) => format!("{META_PREFIX}{key}"),
+            Node::Unary(op, operand) => format!("({op:?} {})", render(operand)),
+            Node::Binary(op, left, right) => format!("({op:?} {} {})", render(left), render(right)),
+            Node::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(render).collect();
+                format!("{name}({})", args.join(", "))
+            }
+        }
+    }
+
+    fn tree(text: &str) -> String {
+        render(&parse(text).unwrap())
+    }
+
+    fn evaluate(text: &str) -> Vec<Option<f64>> {
+        let data = df!("qc (MPa)" => [1.0, 4.0], "fs (kPa)" => [10.0, 20.0]).unwrap();
+        let expr = parse(text).unwrap().compile(&Dataset::new(data.clone())).unwrap();
+        let column = data.lazy().select([expr.cast(DataType::Float64).alias("x")]).collect().unwrap();
+        column.column("x").unwrap().f64().unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn arithmetic_precedence() {
+        assert_eq!(tree("1 + 2 * 3"), "(Add 1 (Mul 2 3))");
+        assert_eq!(tree("(1 + 2) * 3"), "(Mul (Add 1 2) 3)");
+        assert_eq!(tree("1 - 2 - 3"), "(Sub (Sub 1 2) 3)");
+        assert_eq!(tree("8 / 4 / 2"), "(Div (Div 8 4) 2)");
+        assert_eq!(tree("2 ^ 3 ^ 2"), "(Pow 2 (Pow 3 2))");
+        assert_eq!(tree("2 * 3 ^ 2"), "(Mul 2 (Pow 3 2))");
+    }
+
+    #[test]
+    fn logic_precedence() {
+        assert_eq!(tree("a < 1 and b > 2 or c"), "(Or (And (Lt a 1) (Gt b 2)) c)");
+        assert_eq!(tree("not a == 1 && b"), "(And (Not (Eq a 1)) b)");
+        assert_eq!(tree("a = 1 || !b"), "(Or (Eq a 1) (Not b))");
+        assert_eq!(tree("a + 1 >= b * 2"), "(GtEq (Add a 1) (Mul b 2))");
+        assert_eq!(tree("a AND b"), "(And a b)");
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(tree("-2 ^ 2"), "(Neg (Pow 2 2))");
+        assert_eq!(tree("2 ^ -1"), "(Pow 2 (Neg 1))");
+        assert_eq!(tree("--qc"), "(Neg (Neg qc))");
+        assert_eq!(tree("+qc"), "qc");
+        assert_eq!(tree("1 - -2"), "(Sub 1 (Neg 2))");
+        assert_eq!(tree("-a * b"), "(Mul (Neg a) b)");
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(tree("1.5e-3"), "0.0015");
+        assert_eq!(tree(".5"), "0.5");
+        assert_eq!(tree("'qc (MPa)'"), "qc (MPa)");
+        assert_eq!(tree("meta.gwt"), "meta.gwt");
+        assert_eq!(tree("PI"), PI.to_string());
+        assert_eq!(tree("gamma_w"), WATER_UNIT_WEIGHT.to_string());
+    }
+
+    #[test]
+    fn function_calls() {
+        assert_eq!(tree("sqrt(qc)"), "sqrt(qc)");
+        assert_eq!(tree("MAX(qc, fs / 1000, 0)"), "max(qc, (Div fs 1000), 0)");
+        assert_eq!(tree("if(qc > 1, log(qc, 10), -1)"), "if((Gt qc 1), log(qc, 10), (Neg 1))");
+        assert_eq!(tree("min()"), "min()");
+        assert_eq!(tree("abs(-qc) ^ 2"), "(Pow abs((Neg qc)) 2)");
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(parse("qc + * 2").unwrap_err(), "unexpected '*' at position 6");
+        assert_eq!(parse("qc 2").unwrap_err(), "unexpected number '2' at position 4");
+        assert_eq!(parse("qc # 2").unwrap_err(), "unexpected '#' at position 4");
+        assert_eq!(parse("1 + 'qc").unwrap_err(), "unterminated quote at position 5");
+        assert_eq!(parse("1.2.3").unwrap_err(), "invalid number '1.2.3' at position 1");
+        assert_eq!(parse("a and or b").unwrap_err(), "unexpected 'or' at position 7");
+        assert_eq!(parse("sqrt(qc fs)").unwrap_err(), "expected ')' but found 'fs' at position 9");
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(parse("").unwrap_err(), "unexpected end of expression");
+        assert_eq!(parse("qc -").unwrap_err(), "unexpected end of expression");
+        assert_eq!(parse("(qc + 1").unwrap_err(), "expected ')' at the end of the expression");
+        assert_eq!(parse("qc)").unwrap_err(), "unexpected ')' at position 3");
+        assert_eq!(parse("max(qc,)").unwrap_err(), "unexpected ')' at position 8");
+        assert_eq!(parse("()").unwrap_err(), "unexpected ')' at position 2");
+    }
+
+    #[test]
+    fn compile_checks_calls() {
+        let dataset = Dataset::new(df!("qc (MPa)" => [1.0]).unwrap());
+        let compile = |text: &str| parse(text).unwrap().compile(&dataset).unwrap_err();
+
+        assert_eq!(compile("sqrt(qc, 2)"), "'sqrt' takes 1 argument, 2 given");
+        assert_eq!(compile("log(qc)"), "'log' takes 2 arguments, 1 given");
+        assert_eq!(compile("max()"), "'max' takes at least 1 argument");
+        assert_eq!(compile("foo(qc)"), "unknown function 'foo'");
+        assert_eq!(compile("round(qc, qc)"), "'round' takes a constant number of decimals");
+        assert_eq!(compile("round(qc, 1.5)"), "'round' takes a whole number of decimals");
+        assert_eq!(compile("fs + 1"), "column 'fs' not found");
+    }
+
+    #[test]
+    fn evaluation() {
+        assert_eq!(evaluate("qc + fs / 10"), [Some(2.0), Some(6.0)]);
+        assert_eq!(evaluate("-qc ^ 2"), [Some(-1.0), Some(-16.0)]);
+        assert_eq!(evaluate("sqrt('qc (MPa)') * 2"), [Some(2.0), Some(4.0)]);
+        assert_eq!(evaluate("max(qc, 2)"), [Some(2.0), Some(4.0)]);
+        assert_eq!(evaluate("if(qc > 2, 1, 0)"), [Some(0.0), Some(1.0)]);
+        assert_eq!(evaluate("round(log(fs, 10), 2)"), [Some(1.0), Some(1.3)]);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::rx::data::Step;
+use crate::rx::{Dataset, Datasets};
+
+/// Maximum number of operations that can be undone.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// Operations that changed the datasets in memory, shared by the REPL
+/// and batch modes.
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+
+/// State of the datasets around a recorded operation.
+///
+/// Snapshots are cheap, as dataframe columns are reference-counted.
+struct Snapshot {
+    /// Command that performed the operation.
+    label: String,
+    /// Datasets before the operation (undo) or after it (redo).
+    datasets: Datasets,
+}
+
+/// Bounded undo and redo stacks of dataset snapshots.
+struct History {
+    /// Operations that can be undone, oldest first.
+    undo: VecDeque<Snapshot>,
+    /// Operations that can be redone, most recently undone last.
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// Creates an empty history.
+    const fn new() -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new() }
+    }
+}
+
+/// Whether a recorded operation is in effect or has been undone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    /// The operation can be undone.
+    Done,
+    /// The operation can be redone.
+    Undone,
+}
+
+/// Locks the history, recovering it if a command panicked while
+/// holding it.
+fn history() -> MutexGuard<'static, History> {
+    HISTORY.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Records `label` as an operation if it changed the datasets from
+/// `before`, discarding the operations that could be redone.
+pub fn record(label: &str, before: Datasets, after: &Datasets) {
+    if !changed(&before, after) {
+        return;
+    }
+
+    let mut history = history();
+    history.redo.clear();
+    history.undo.push_back(Snapshot { label: label.to_string(), datasets: before });
+    if history.undo.len() > HISTORY_LIMIT {
+        history.undo.pop_front();
+    }
+}
+
+/// Reverts up to `steps` operations, returning the labels of those undone.
+pub fn undo(steps: usize, datasets: &mut Datasets) -> Vec<String> {
+    let mut history = history();
+    let mut labels = Vec::new();
+
+    for _ in 0..steps {
+        let Some(mut snapshot) = history.undo.pop_back() else { break };
+        mem::swap(&mut snapshot.datasets, datasets);
+        labels.push(snapshot.label.clone());
+        history.redo.push(snapshot);
+    }
+
+    labels
+}
+
+/// Reapplies up to `steps` undone operations, returning the labels of
+/// those redone.
+pub fn redo(steps: usize, datasets: &mut Datasets) -> Vec<String> {
+    let mut history = history();
+    let mut labels = Vec::new();
+
+    for _ in 0..steps {
+        let Some(mut snapshot) = history.redo.pop() else { break };
+        mem::swap(&mut snapshot.datasets, datasets);
+        labels.push(snapshot.label.clone());
+        history.undo.push_back(snapshot);
+    }
+
+    labels
+}
+
+/// Returns the recorded operations in the order they were performed.
+pub fn entries() -> Vec<(String, State)> {
+    let history = history();
+    let done = history.undo.iter().map(|s| (s.label.clone(), State::Done));
+    let undone = history.redo.iter().rev().map(|s| (s.label.clone(), State::Undone));
+    done.chain(undone).collect()
+}
+
+/// Appends `label` to the provenance of the datasets that it added or
+/// modified from `before`.
+pub fn trace(label: &str, before: &Datasets, after: &mut Datasets) {
+    let seq = before
+        .values()
+        .chain(after.values())
+        .map(|dataset| dataset.provenance.next_seq())
+        .max()
+        .unwrap_or(0);
+
+    for (name, dataset) in after.iter_mut() {
+        if modified(before.get(name), dataset) {
+            let step = Step { seq, command: label.to_string() };
+            dataset.provenance.steps.push(step);
+        }
+    }
+}
+
+/// Returns the sorted names of the datasets added or modified from
+/// `before`.
+pub fn changes(before: &Datasets, after: &Datasets) -> Vec<String> {
+    let mut names: Vec<String> = after
+        .iter()
+        .filter(|(name, dataset)| modified(before.get(*name), dataset))
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Returns whether any dataset was added, removed, renamed or modified.
+fn changed(before: &Datasets, after: &Datasets) -> bool {
+    before.len() != after.len()
+        || before.keys().any(|name| !after.contains_key(name))
+        || after.iter().any(|(name, dataset)| modified(before.get(name), dataset))
+}
+
+/// Returns whether `dataset` is new or differs from its `previous` state,
+/// in metadata, column names and types, or values.
+fn modified(previous: Option<&Dataset>, dataset: &Dataset) -> bool {
+    previous.is_none_or(|previous| {
+        previous.meta != dataset.meta
+            || previous.data.schema() != dataset.data.schema()
+            || !previous.data.equals_missing(&dataset.data)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn datasets(entries: &[(&str, DataFrame)]) -> Datasets {
+        entries.iter().map(|(name, df)| (name.to_string(), Dataset::new(df.clone()))).collect()
+    }
+
+    #[test]
+    fn renames_are_changes() {
+        let df = df!("depth (m)" => [0.1, 0.2]).unwrap();
+        let before = datasets(&[("A", df.clone())]);
+
+        assert!(changed(&before, &datasets(&[("B", df.clone())])));
+        assert!(!changed(&before, &datasets(&[("A", df)])));
+    }
+
+    #[test]
+    fn column_names_and_types_are_changes() {
+        let df = df!("depth (m)" => [0.1, 0.2], "n" => [1i64, 2]).unwrap();
+        let before = Dataset::new(df.clone());
+
+        let mut renamed = df.clone();
+        renamed.rename("n", "count".into()).unwrap();
+        assert!(modified(Some(&before), &Dataset::new(renamed)));
+
+        let cast = df.lazy().with_column(col("n").cast(DataType::Float64)).collect().unwrap();
+        assert!(modified(Some(&before), &Dataset::new(cast)));
+    }
+}
@@ -15,13 +15,17 @@ use tabled::{
     settings::style::BorderColor,
 };
 
+use crate::rx::config;
+use crate::rx::data::split_column_name;
+
 /// Prompt symbol for the REPL interface.
 pub const PROMPT: &str = "❯";
 
-/// Maximum width for app rendering.
+/// Maximum width for app rendering, unless set by `display.width`.
 const APP_WIDTH_LIMIT: usize = 88;
 
-/// Number of decimal places to show in tables.
+/// Default number of decimal places to show in tables, unless set by
+/// `display.precision`.
 const TABLE_FLOAT_PRECISION: usize = 2;
 
 /// Prints a styled header banner above the REPL interface.
@@ -91,97 +95,201 @@ pub fn print_error(msg: impl Display) {
     print_styled_msg(label, msg, true);
 }
 
-/// Determines the width to use for the header, limited by APP_WIDTH_LIMIT.
+/// Determines the width to use for the header, limited by APP_WIDTH_LIMIT
+/// or the configured width.
 fn app_width() -> usize {
+    let limit = config::get().display.width.unwrap_or(APP_WIDTH_LIMIT);
     terminal::size()
-        .map(|(width, _)| usize::min(APP_WIDTH_LIMIT, width as usize))
-        .unwrap_or(APP_WIDTH_LIMIT)
+        .map(|(width, _)| usize::min(limit, width as usize))
+        .unwrap_or(limit)
+}
+
+/// Rows of a dataframe shown by [`print_table`].
+#[derive(Debug, Clone, Copy)]
+pub enum TableRows {
+    /// First N rows.
+    Head(usize),
+    /// Last N rows.
+    Tail(usize),
+    /// First and last N rows, separated by an ellipsis row.
+    HeadTail(usize),
+}
+
+/// Number of decimal places used to render each table column.
+#[derive(Debug, Clone, Default)]
+pub struct Precision {
+    /// Decimal places for columns without a specific setting.
+    pub default: Option<usize>,
+    /// Decimal places by column label or full name.
+    pub columns: Vec<(String, usize)>,
+}
+
+impl Precision {
+    /// Builds the settings from `(column, places)` pairs, where a pair
+    /// without column sets the default.
+    pub fn from_pairs(pairs: &[(Option<String>, usize)]) -> Self {
+        let mut precision = Self::default();
+        for (column, places) in pairs {
+            match column {
+                Some(column) => precision.columns.push((column.clone(), *places)),
+                None => precision.default = Some(*places),
+            }
+        }
+        precision
+    }
+
+    /// Returns the decimal places to use for `column`.
+    pub fn of(&self, column: &str) -> usize {
+        let (label, _) = split_column_name(column);
+        self.columns
+            .iter()
+            .rev()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&label) || name == column)
+            .map(|(_, places)| *places)
+            .or(self.default)
+            .or(config::get().display.precision)
+            .unwrap_or(TABLE_FLOAT_PRECISION)
+    }
 }
 
 /// Prints a dataframe as a formatted table with headers and units.
 ///
-/// The table shows either first or last N rows based on the sign of `nrows`.
-pub fn print_table(data: &DataFrame, name: &str, nrows: isize) {
-    let count = nrows.unsigned_abs();
+/// Requests for more rows than available show the whole dataframe.
+pub fn print_table(data: &DataFrame, name: &str, rows: TableRows, precision: &Precision) {
     let total_rows = data.height();
 
-    if count > total_rows {
-        print_error("cannot print more rows than dataset height");
-        return
-    }
+    let (count, prefix) = match rows {
+        TableRows::Head(n) => (n, "first"),
+        TableRows::Tail(n) => (n, "last"),
+        TableRows::HeadTail(n) => (n, "first and last"),
+    };
 
-    let sliced = if nrows > 0 {
-            data.head(Some(count))
-        } else {
-            data.tail(Some(count))
-        };
+    let shows_all = match rows {
+        TableRows::HeadTail(n) => 2 * n >= total_rows,
+        _ => count >= total_rows,
+    };
+
+    let records: Vec<Vec<String>> = if shows_all {
+        build_rows(data, precision)
+    } else {
+        match rows {
+            TableRows::Head(n) => build_rows(&data.head(Some(n)), precision),
+            TableRows::Tail(n) => build_rows(&data.tail(Some(n)), precision),
+            TableRows::HeadTail(n) => {
+                let ellipsis = vec!["⋮".to_string(); data.width()];
+                let mut records = build_rows(&data.head(Some(n)), precision);
+                records.push(ellipsis);
+                records.extend(build_rows(&data.tail(Some(n)), precision));
+                records
+            }
+        }
+    };
 
     // extract labels and units from column names
     let (labels, units): (Vec<String>, Vec<String>) = data
         .get_column_names()
         .iter()
-        .map(|s| {
-            if let Some((label, unit)) = s.rsplit_once('(') {
-                (
-                    label.trim().to_string(),
-                    format!("({})", unit.trim().trim_end_matches(')')),
-                )
-            } else {
-                // fallback if there's no unit
-                (s.to_string(), String::new())
-            }
-        })
+        .map(|s| split_column_name(s))
         .unzip();
 
-    let mut rows: Vec<Vec<String>> = (0..sliced.height())
-        .filter_map(|i| build_row(i, &sliced))
-        .collect();
+    let subtitle = if shows_all {
+        let plural = if total_rows == 1 { "row" } else { "rows" };
+        format!("(all {total_rows} {plural})")
+    } else {
+        let plural = if count == 1 { "row" } else { "rows" };
+        format!("({prefix} {count} {plural} of {total_rows})")
+    };
+
+    print_labeled_records(name, &subtitle, labels, units, records);
+}
 
-    rows.insert(0, labels);
-    rows.insert(1, units);
+/// Prints records as a formatted table headed by labels and units.
+///
+/// Shares the styling of [`print_table`] for content that is not a
+/// plain slice of a dataframe.
+pub fn print_labeled_records(
+    name: &str,
+    subtitle: &str,
+    labels: Vec<String>,
+    units: Vec<String>,
+    mut records: Vec<Vec<String>>,
+) {
+    records.insert(0, labels);
+    records.insert(1, units);
+    print_styled(name, subtitle, records, 2, Alignment::right());
+}
 
+/// Prints records under a title line as a table whose first
+/// `header_rows` rows are headers, styled alike across the app.
+///
+/// Headers spanning several rows, such as labels over units, are
+/// centered and underlined only below the last one.
+fn print_styled(title: &str, subtitle: &str, records: Vec<Vec<String>>, header_rows: usize, alignment: Alignment) {
     let mut builder = builder::Builder::default();
-    for row in rows {
+    for row in records {
         builder.push_record(row);
     }
 
     // visual table tweaks
+    let last_header = header_rows.saturating_sub(1);
     let mut table = builder.build();
+    table.with(Style::re_structured_text()).with(alignment);
+    if header_rows > 1 {
+        table.modify(Rows::one(0), Border::empty());
+    }
     table
-        .with(Style::re_structured_text())
-        .with(Alignment::right())
-        .modify(Rows::one(0), Border::empty())
         .modify(Rows::one(0), Color::BOLD)
-        .modify(Rows::one(0), BorderColor::filled(Color::FG_BRIGHT_BLACK))
-        .modify(Rows::one(0), Alignment::center())
-        .modify(Rows::one(1), Border::new().bottom('='))
-        .modify(Rows::one(1), BorderColor::filled(Color::FG_BRIGHT_BLACK))
-        .modify(Rows::one(1), Alignment::center())
-        .modify(Rows::last(), BorderColor::filled(Color::FG_BRIGHT_BLACK))
-        .with(Width::increase(app_width()));
-        
-
-    let plural = if count == 1 { "row" } else { "rows" };
-    let prefix = if nrows > 0 { "first" } else { "last" };
-    let subtitle = format!("({prefix} {count} {plural})");
-    let title = format!("\n {PROMPT} {PROMPT} {name}");
+        .modify(Rows::one(last_header), Border::new().bottom('='))
+        .modify(Rows::new(..header_rows), BorderColor::filled(Color::FG_BRIGHT_BLACK))
+        .modify(Rows::last(), BorderColor::filled(Color::FG_BRIGHT_BLACK));
+    if header_rows > 1 {
+        table.modify(Rows::new(..header_rows), Alignment::center());
+    }
+    table.with(Width::increase(app_width()));
+
+    let title = format!("\n {PROMPT} {PROMPT} {title}");
 
     println!("{} {}", title.bold(), subtitle);
     println!("{table}");
 }
 
+/// Prints a list of records as a formatted table with a single header row.
+///
+/// Used for summaries that are not backed by a dataframe.
+pub fn print_records(title: &str, subtitle: &str, headers: Vec<String>, mut records: Vec<Vec<String>>) {
+    records.insert(0, headers);
+    print_styled(title, subtitle, records, 1, Alignment::left());
+}
+
+/// Builds table rows from DataFrame values.
+fn build_rows(df: &DataFrame, precision: &Precision) -> Vec<Vec<String>> {
+    let places: Vec<usize> = df
+        .get_column_names()
+        .iter()
+        .map(|name| precision.of(name))
+        .collect();
+
+    (0..df.height())
+        .filter_map(|i| build_row(i, df, &places))
+        .collect()
+}
+
 /// Builds a single table row from DataFrame values.
-fn build_row(i: usize, df: &DataFrame) -> Option<Vec<String>> {
-    df.get_columns().iter().map(|col| {
+fn build_row(i: usize, df: &DataFrame, places: &[usize]) -> Option<Vec<String>> {
+    df.get_columns().iter().zip(places).map(|(col, &prec)| {
         let val = col.get(i).inspect_err(|_| {
             print_error(format!("missing value at row {i}"));
         }).ok()?;
 
-        let fval = val.try_extract::<f64>().inspect_err(|_| {
-            print_error(format!("non-f64 value at row {i}"));
-        }).ok()?;
+        if val.is_null() {
+            return Some("null".to_string());
+        }
 
-        Some(format!("{fval:.TABLE_FLOAT_PRECISION$}"))
+        // non-numeric values, such as dataset names, are shown as text
+        match val.try_extract::<f64>() {
+            Ok(fval) => Some(format!("{fval:.prec$}")),
+            Err(_) => Some(val.str_value().to_string()),
+        }
     }).collect()
 }
 
@@ -1,9 +1,15 @@
 use std::collections::HashMap;
-use polars::prelude::DataFrame;
 
 /// Map of named datasets stored in memory.
-pub type Datasets = HashMap<String, DataFrame>;
+pub type Datasets = HashMap<String, Dataset>;
 
+pub mod batch;
 pub mod cmd;
+pub mod config;
+pub mod data;
+pub mod expr;
+pub mod history;
 pub mod io;
-pub mod repl;
\ No newline at end of file
+pub mod repl;
+
+pub use data::Dataset;
\ No newline at end of file
@@ -1,30 +1,195 @@
 use std::borrow::Cow;
 
+use clap::{Arg, Command};
 use crossterm::style::{Color, Stylize};
 use rustyline::{
     CompletionType, Context, Helper, Result,
     completion::{Completer, FilenameCompleter, Pair},
     highlight::{Highlighter, CmdKind},
-    hint::Hinter,
-    validate::Validator,
+    hint::{Hint, Hinter, HistoryHinter},
+    validate::{ValidationContext, ValidationResult, Validator},
 };
 
+use crate::rx::Datasets;
+use crate::rx::cmd;
+use crate::rx::config::{self, Kind};
+use crate::rx::data::{META_KEYS, split_column_name};
+
+/// Value names of arguments completed with file paths.
+const PATH_VALUES: [&str; 4] = ["FILE", "DIR", "PATH", "SCRIPT"];
+
+/// Value names of arguments completed with dataset names.
+const DATASET_VALUES: [&str; 5] = ["NAME", "PATTERN", "LEFT", "RIGHT", "DATASET"];
+
+/// Value names of arguments completed with column labels.
+const COLUMN_VALUES: [&str; 3] = ["COLUMN", "EXPRESSION", "CONDITION"];
+
 /// Helper for REPL, implementing completion, highlighting, and validation.
 pub struct ReadLineHelper {
-    /// List of supported commands used for autocompletion.
-    commands: Vec<String>,
+    /// Tree of REPL commands and their arguments.
+    cli: Command,
+    /// Datasets in memory, offered as names and columns.
+    datasets: Datasets,
     /// Completer for file paths.
     file_completer: FilenameCompleter,
+    /// Hinter suggesting previous lines that start with the input.
+    history_hinter: HistoryHinter,
 }
 
 impl ReadLineHelper {
-    /// Create a new helper with the given commands.
-    pub fn new(commands: Vec<String>) -> Self {
+    /// Create a new helper for the REPL commands.
+    pub fn new() -> Self {
+        let mut cli = cmd::repl_command();
+        cli.build();
+
         Self {
-            commands,
+            cli,
+            datasets: Datasets::new(),
             file_completer: FilenameCompleter::new(),
+            history_hinter: HistoryHinter::new(),
         }
     }
+
+    /// Refreshes the datasets offered as completions.
+    pub fn update(&mut self, datasets: &Datasets) {
+        self.datasets = datasets.clone();
+    }
+
+    /// Lists the values of `arg` from its possible values or, by its value
+    /// name, from datasets, columns or metadata keys.
+    ///
+    /// Returns `None` for arguments completed with file paths.
+    fn values(&self, arg: &Arg, pattern: Option<&str>, ignore_case: bool) -> Option<Vec<String>> {
+        let values = arg.get_possible_values();
+        if !values.is_empty() {
+            let names = values.iter().filter(|v| !v.is_hide_set()).map(|v| v.get_name());
+            return Some(names.map(String::from).collect());
+        }
+
+        let value_name = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map_or(arg.get_id().as_str(), |name| name.as_str());
+
+        let candidates = match value_name {
+            // paths are completed by the caller
+            name if PATH_VALUES.contains(&name) => return None,
+            // new names are not worth suggesting
+            _ if arg.get_long() == Some("as") => Vec::new(),
+            name if DATASET_VALUES.contains(&name) => {
+                let mut names: Vec<String> = self.datasets.keys().cloned().collect();
+                names.sort();
+                names
+            }
+            name if COLUMN_VALUES.contains(&name) => {
+                let names = pattern.map_or_else(Vec::new, |p| cmd::select(&self.datasets, p, ignore_case));
+                let mut labels: Vec<String> = names
+                    .iter()
+                    .flat_map(|name| self.datasets[name].data.get_column_names_owned())
+                    .map(|column| {
+                        let (label, _) = split_column_name(&column);
+                        // labels with spaces must be quoted
+                        if label.contains(char::is_whitespace) { format!("'{label}'") } else { label }
+                    })
+                    .collect();
+                labels.sort();
+                labels
+            }
+            "KEY" => META_KEYS.iter().map(|(key, _)| key.to_string()).collect(),
+            "KEY=VALUE" => META_KEYS.iter().map(|(key, _)| format!("{key}=")).collect(),
+            "COMMAND" => self.cli.get_subcommands().map(|c| c.get_name().to_string()).collect(),
+            "ALIAS" => self.defined(Kind::Alias),
+            "MACRO" => self.defined(Kind::Macro),
+            _ => Vec::new(),
+        };
+        Some(candidates)
+    }
+
+    /// Lists the names of the aliases or macros defined, sorted.
+    fn defined(&self, kind: Kind) -> Vec<String> {
+        let mut names: Vec<String> = config::get()
+            .definitions(kind)
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Returns whether `name` is a command or an alias.
+    fn is_command(&self, name: &str) -> bool {
+        self.cli.find_subcommand(name).is_some() || config::get().lookup(Kind::Alias, name).is_some()
+    }
+
+    /// Finds the command named by the leading tokens, descending into
+    /// nested subcommands such as `session save`, and returns it with the
+    /// tokens that follow.
+    ///
+    /// A command with subcommands is returned when none has been typed.
+    fn resolve<'t>(&self, tokens: &'t [&'t str]) -> Option<(&Command, &'t [&'t str])> {
+        let (name, mut args) = tokens.split_first()?;
+        let mut command = self.cli.find_subcommand(name)?;
+        while command.has_subcommands() {
+            let Some((name, rest)) = args.split_first() else { break };
+            command = command.find_subcommand(name)?;
+            args = rest;
+        }
+        Some((command, args))
+    }
+
+    /// Describes the arguments still expected after `input`, such as
+    /// `<NAME> <PROPERTY>...`, leaving out the one given by a pipe.
+    fn signature(&self, input: &str, piped: bool) -> Option<String> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let (command, args) = self.resolve(&tokens)?;
+
+        let text = if command.has_subcommands() {
+            let names: Vec<&str> = command.get_subcommands().map(|c| c.get_name()).collect();
+            format!("<{}>", names.join("|"))
+        } else {
+            let slot = Slot::locate(command, args, piped);
+            let mut parts = Vec::new();
+            if let Some(option) = slot.pending.filter(|_| !slot.given) {
+                parts.push(describe(option));
+            }
+            let skip = slot.index + usize::from(slot.pending.is_none() && slot.given);
+            parts.extend(command.get_positionals().skip(skip).map(describe));
+            parts.join(" ")
+        };
+
+        if text.is_empty() {
+            return None;
+        }
+        // separate the hint from a command name typed alone
+        let space = if input.ends_with(char::is_whitespace) { "" } else { " " };
+        Some(format!("{space}{text}"))
+    }
+
+    /// Checks that every command of a line can be split into tokens and
+    /// names a known command, which takes datasets if it is piped.
+    fn check(&self, line: &str) -> std::result::Result<(), String> {
+        let stages = cmd::split_commands(line)
+            .into_iter()
+            .flat_map(|command| cmd::split_pipeline(&command).into_iter().enumerate());
+
+        for (i, stage) in stages {
+            let Some(tokens) = shlex::split(&stage) else {
+                return Err("unbalanced quotes".into());
+            };
+            let Some(name) = tokens.first() else {
+                return Err("empty command in pipeline".into());
+            };
+            if !self.is_command(name) {
+                return Err(format!("unknown command '{name}'"));
+            }
+            // aliases are checked once expanded
+            if i > 0 && self.cli.find_subcommand(name).is_some_and(|c| !cmd::takes_piped(c)) {
+                return Err(format!("'{name}' does not take datasets from a pipe"));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Helper for ReadLineHelper {}
@@ -38,84 +203,262 @@ impl Completer for ReadLineHelper {
         pos: usize,
         ctx: &Context<'_>,
     ) -> Result<(usize, Vec<Pair>)> {
-        // slice input text up to current cursor position
+        // slice input text up to current cursor position, keeping the
+        // command being typed after any `;` or `|`
         let input = &line[..pos];
-        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let (offset, piped) = cmd::last_stage(input);
+        let start = input[offset..]
+            .rfind(char::is_whitespace)
+            .map_or(offset, |i| offset + i + 1);
+        let word = &input[start..];
+        let tokens: Vec<&str> = input[offset..start].split_whitespace().collect();
 
+        // complete first token for commands and aliases
         if tokens.is_empty() {
-            return Ok((0, Vec::new()));
+            let names = self.cli.get_subcommands().map(|c| c.get_name().to_string());
+            let names = names.chain(self.defined(Kind::Alias));
+            return Ok((start, pairs(names.filter(|n| n.starts_with(word)))));
+        }
+        let Some((command, args)) = self.resolve(&tokens) else {
+            return Ok((start, Vec::new()));
+        };
+
+        // complete names of nested subcommands, such as `session save`
+        if command.has_subcommands() {
+            let names = command.get_subcommands().map(|c| c.get_name().to_string());
+            return Ok((start, pairs(names.filter(|n| n.starts_with(word)))));
+        }
+
+        // complete flags of the command
+        if word.starts_with("--") || word == "-" {
+            let flags = command
+                .get_arguments()
+                .filter(|a| !a.is_hide_set())
+                .filter_map(|a| a.get_long().map(|long| format!("--{long}")));
+            return Ok((start, pairs(flags.filter(|f| f.starts_with(word)))));
+        }
+
+        let slot = Slot::locate(command, args, piped);
+        let Some(arg) = slot.arg else {
+            return Ok((start, Vec::new()));
+        };
+
+        match self.values(arg, slot.pattern, slot.ignore_case) {
+            Some(values) => {
+                // values such as dataset names are matched regardless of case
+                let word = word.to_ascii_lowercase();
+                let matching = values.into_iter().filter(|v| v.to_ascii_lowercase().starts_with(&word));
+                Ok((start, pairs(matching)))
+            }
+            None => self.file_completer.complete(line, pos, ctx),
         }
+    }
+}
 
-        // complete first token for commands
-        if tokens.len() == 1 && !line.ends_with(' ') {
-            let prefix = tokens[0];
-            let candidates = self
-                .commands
-                .iter()
-                .filter(|cmd| cmd.starts_with(prefix))
-                .map(|cmd| Pair {
-                    display: cmd.clone(),
-                    replacement: cmd.clone(),
-                })
-                .collect();
-            return Ok((0, candidates));
+/// Argument being typed, located from the preceding tokens.
+struct Slot<'a> {
+    /// Argument receiving the value, if any.
+    arg: Option<&'a Arg>,
+    /// Value of the first positional argument, which names the datasets.
+    pattern: Option<&'a str>,
+    /// Whether dataset names match regardless of case.
+    ignore_case: bool,
+    /// Option waiting for its values, if any.
+    pending: Option<&'a Arg>,
+    /// Index of the positional argument to be given next.
+    index: usize,
+    /// Whether the argument receiving values already has one.
+    given: bool,
+}
+
+impl<'a> Slot<'a> {
+    /// Follows the tokens typed after the command, tracking options that
+    /// take values and the positional arguments already given.
+    ///
+    /// A `piped` command already has its first positional argument.
+    fn locate(command: &'a Command, args: &[&'a str], piped: bool) -> Self {
+        let positionals: Vec<&Arg> = command.get_positionals().collect();
+        let mut slot = Slot {
+            arg: None,
+            pattern: None,
+            ignore_case: false,
+            pending: None,
+            index: 0,
+            given: false,
+        };
+        if piped {
+            match positionals.first() {
+                Some(arg) if takes_many(arg) => slot.given = true,
+                Some(_) => slot.index = 1,
+                None => {}
+            }
         }
 
-        // provide autocompletion for file/directory arguments in 'load' command
-        if tokens[0] == "load"
-            && tokens.len() >= 2
-            && matches!(tokens[1], "-f" | "--file" | "-d" | "--dir")
-            && (tokens.len() == 3 || (tokens.len() == 2 && line.ends_with(' ')))
-        {
-            return self.file_completer.complete(line, pos, ctx);
+        for &token in args {
+            let is_flag = token.starts_with('-') && token.parse::<f64>().is_err();
+            if is_flag {
+                let option = find_option(command, token);
+                slot.ignore_case |= option.is_some_and(|a| a.get_id() == "ignore_case");
+                slot.pending = option.filter(|a| takes_values(a) && !token.contains('='));
+                slot.given = false;
+                continue;
+            }
+
+            // options taking several values keep the following tokens
+            if let Some(option) = slot.pending {
+                if takes_many(option) {
+                    slot.given = true;
+                } else {
+                    slot.pending = None;
+                }
+                continue;
+            }
+
+            if slot.index == 0 {
+                slot.pattern = Some(token);
+            }
+            match positionals.get(slot.index) {
+                Some(arg) if takes_many(arg) => slot.given = true,
+                Some(_) => {
+                    slot.index += 1;
+                    slot.given = false;
+                }
+                None => {}
+            }
         }
 
-        // no suggestions
-        Ok((0, Vec::new()))
+        slot.arg = slot.pending.or_else(|| positionals.get(slot.index).copied());
+        slot
+    }
+}
+
+/// Finds the option named by a `--long`, `--long=value` or `-s` token.
+fn find_option<'a>(command: &'a Command, token: &str) -> Option<&'a Arg> {
+    if let Some(long) = token.strip_prefix("--") {
+        let long = long.split('=').next().unwrap_or(long);
+        return command.get_arguments().find(|a| a.get_long() == Some(long));
+    }
+
+    let short = token.strip_prefix('-')?.chars().next()?;
+    command.get_arguments().find(|a| a.get_short() == Some(short))
+}
+
+/// Returns whether an argument takes values.
+fn takes_values(arg: &Arg) -> bool {
+    arg.get_num_args().is_some_and(|n| n.takes_values())
+}
+
+/// Returns whether an argument takes more than one value.
+fn takes_many(arg: &Arg) -> bool {
+    arg.get_num_args().is_some_and(|n| n.max_values() > 1)
+}
+
+/// Describes an argument as `<VALUE>`, or `[VALUE]` when optional,
+/// followed by `...` when it takes several values.
+fn describe(arg: &Arg) -> String {
+    let name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(|| arg.get_id().as_str().to_uppercase(), |name| name.to_string());
+    let many = if takes_many(arg) { "..." } else { "" };
+    if arg.is_required_set() || arg.get_long().is_some() || arg.get_short().is_some() {
+        format!("<{name}>{many}")
+    } else {
+        format!("[{name}]{many}")
+    }
+}
+
+/// Builds completion candidates, keeping the first of repeated ones.
+fn pairs(candidates: impl Iterator<Item = String>) -> Vec<Pair> {
+    let mut unique: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if !unique.contains(&candidate) {
+            unique.push(candidate);
+        }
+    }
+    unique
+        .into_iter()
+        .map(|c| Pair { display: c.clone(), replacement: c })
+        .collect()
+}
+
+/// Greyed-out text shown after the cursor.
+pub struct InlineHint {
+    /// Text to display.
+    text: String,
+    /// Whether the text completes the line when accepted, as history
+    /// suggestions do; signatures are only shown.
+    accept: bool,
+}
+
+impl Hint for InlineHint {
+    fn display(&self) -> &str {
+        &self.text
+    }
+
+    fn completion(&self) -> Option<&str> {
+        self.accept.then_some(self.text.as_str())
     }
 }
 
 impl Hinter for ReadLineHelper {
-    type Hint = String;
+    type Hint = InlineHint;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<InlineHint> {
+        // hint only at the end of the line
+        if pos < line.len() || line.trim().is_empty() {
+            return None;
+        }
 
-    fn hint(&self, _line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<String> {
-        None  // no hints
+        // prefer lines from history, then the arguments still expected
+        if let Some(text) = self.history_hinter.hint(line, pos, ctx) {
+            return Some(InlineHint { text, accept: true });
+        }
+        let (offset, piped) = cmd::last_stage(line);
+        self.signature(&line[offset..], piped)
+            .map(|text| InlineHint { text, accept: false })
     }
 }
 
-impl Validator for ReadLineHelper {}
+impl Validator for ReadLineHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult> {
+        Ok(match self.check(ctx.input()) {
+            Ok(()) => ValidationResult::Valid(None),
+            Err(err) => {
+                let message = format!("  {}", format!("error: {err}").with(Color::Red));
+                ValidationResult::Invalid(Some(message))
+            }
+        })
+    }
+}
 
 impl Highlighter for ReadLineHelper {
     fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
-        // skip empty lines
-        let trimmed = line.trim_start();
-        if trimmed.is_empty() {
-            return Cow::Borrowed(line);
-        }
+        // commands start the line and follow each `;` or `|`
+        let mut styled = String::with_capacity(line.len());
+        let mut start = 0;
+        let mut found = false;
 
-        let first_non_space = match line.find(|c: char| !c.is_whitespace()) {
-            Some(i) => i,
-            None => return Cow::Borrowed(line),
-        };
-        let rest = &line[first_non_space..];
-        let end_rel = rest.find(char::is_whitespace).unwrap_or(rest.len());
-        let end_of_token = first_non_space + end_rel;
-        let token = &line[first_non_space..end_of_token];
-
-        // only highlight exact command matches
-        if !self.commands.iter().any(|c| c.eq(token)) {
-            return Cow::Borrowed(line);
-        }
+        for end in cmd::stage_starts(line).into_iter().skip(1).chain([line.len()]) {
+            let stage = &line[start..end];
+            let first = stage.find(|c: char| !c.is_whitespace()).unwrap_or(stage.len());
+            let rest = &stage[first..];
+            let token_end = first + rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let token = &stage[first..token_end];
 
-        // bold and color the token for display
-        let styled_token = token
-            .bold()
-            .with(Color::Green)  // change to preference
-            .to_string();
+            // only highlight exact command or alias matches
+            if !token.is_empty() && self.is_command(token) {
+                found = true;
+                styled.push_str(&stage[..first]);
+                styled.push_str(&token.bold().with(Color::Green).to_string());
+                styled.push_str(&stage[token_end..]);
+            } else {
+                styled.push_str(stage);
+            }
+            start = end;
+        }
 
-        let leading = &line[..first_non_space];
-        let trailing = &line[end_of_token..];
-        Cow::Owned(format!("{leading}{styled_token}{trailing}"))
+        if found { Cow::Owned(styled) } else { Cow::Borrowed(line) }
     }
 
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
@@ -128,6 +471,10 @@ impl Highlighter for ReadLineHelper {
         Cow::Owned(colored)
     }
 
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.with(Color::DarkGrey).to_string())
+    }
+
     fn highlight_candidate<'c>(
         &self,
         candidate: &'c str,